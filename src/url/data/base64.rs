@@ -0,0 +1,107 @@
+//! The standard base64 encoding of RFC 4648, with padding, as used by `data:`
+//! URLs.
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// INVALID marks the bytes out of the alphabet in DECODE.
+const INVALID: u8 = 0xff;
+
+const DECODE: [u8; 256] = {
+    let mut out = [INVALID; 256];
+    let mut i = 0;
+    while i < ALPHABET.len() {
+        out[ALPHABET[i] as usize] = i as u8;
+        i += 1;
+    }
+    out
+};
+
+/// encoded_len returns the length of the padded encoding of n bytes.
+pub fn encoded_len(n: usize) -> usize {
+    match n % 3 {
+        0 => n / 3 * 4,
+        _ => n / 3 * 4 + 4,
+    }
+}
+
+/// encode_into appends the padded encoding of src to out.
+pub fn encode_into(src: &[u8], out: &mut String) {
+    out.reserve(encoded_len(src.len()));
+
+    let mut chunks = src.chunks_exact(3);
+    for c in &mut chunks {
+        let n = (u32::from(c[0]) << 16) | (u32::from(c[1]) << 8) | u32::from(c[2]);
+        for shift in &[18, 12, 6, 0] {
+            out.push(ALPHABET[(n >> shift) as usize & 0x3f] as char);
+        }
+    }
+
+    match *chunks.remainder() {
+        [a] => {
+            let n = u32::from(a) << 16;
+            out.push(ALPHABET[(n >> 18) as usize & 0x3f] as char);
+            out.push(ALPHABET[(n >> 12) as usize & 0x3f] as char);
+            out.push_str("==");
+        }
+        [a, b] => {
+            let n = (u32::from(a) << 16) | (u32::from(b) << 8);
+            out.push(ALPHABET[(n >> 18) as usize & 0x3f] as char);
+            out.push(ALPHABET[(n >> 12) as usize & 0x3f] as char);
+            out.push(ALPHABET[(n >> 6) as usize & 0x3f] as char);
+            out.push('=');
+        }
+        _ => {}
+    }
+}
+
+/// encode returns the padded encoding of src.
+pub fn encode(src: &[u8]) -> String {
+    let mut out = String::new();
+    encode_into(src, &mut out);
+    out
+}
+
+/// decode decodes src, skipping ASCII whitespace as the forgiving-base64
+/// decoding of the WHATWG Infra standard does. The padding may be left out,
+/// but if present it must complete the last group. On failure it returns the
+/// offset in src of the offending byte, or src.len() if the input is cut in
+/// the middle of a byte.
+pub fn decode(src: &[u8]) -> Result<Vec<u8>, usize> {
+    let mut out = Vec::with_capacity(src.len() / 4 * 3 + 2);
+
+    let (mut acc, mut bits, mut pad) = (0u32, 0u32, 0usize);
+    let mut groups = 0usize;
+    for (i, &c) in src.iter().enumerate() {
+        if c.is_ascii_whitespace() {
+            continue;
+        }
+
+        if c == b'=' {
+            pad += 1;
+            if pad > 2 || groups % 4 < 2 {
+                return Err(i);
+            }
+            continue;
+        }
+
+        let v = DECODE[c as usize];
+        if v == INVALID || pad > 0 {
+            return Err(i);
+        }
+
+        acc = (acc << 6) | u32::from(v);
+        bits += 6;
+        groups += 1;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+
+    match groups % 4 {
+        1 => Err(src.len()),
+        2 | 3 if pad > 0 && pad + groups % 4 != 4 => Err(src.len()),
+        _ => Ok(out),
+    }
+}
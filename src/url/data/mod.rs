@@ -0,0 +1,440 @@
+//! Support of the `data:` URLs of RFC 2397, which carry their content inline:
+//!
+//! ```text
+//! data:[<mediatype>][;base64],<data>
+//! ```
+
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+use super::errors::{self, Error};
+use super::internal::{self, Encoding};
+use super::URL;
+
+pub(super) mod base64;
+
+/// DataURL is the content of a `data:` URL: its media type with parameters,
+/// and its body decoded from base64 or %-escapes.
+///
+/// As told by RFC 2397, a URL without media type, such as "data:,hi", is read
+/// as "text/plain;charset=US-ASCII". The media type and the parameter names are
+/// lowercased, while the parameter values are unescaped as they are.
+///
+/// # Example
+///
+/// ```
+/// use net::url::{self, DataURL};
+///
+/// let u = url::parse("data:text/plain;charset=utf-8;base64,SGVsbG8=").unwrap();
+/// let d = DataURL::from_url(&u).unwrap();
+///
+/// assert_eq!("text/plain", d.media_type());
+/// assert_eq!(Some("utf-8"), d.param("charset"));
+/// assert_eq!(b"Hello", d.data());
+///
+/// let d: DataURL = "data:,A%20brief%20note".parse().unwrap();
+/// assert_eq!("text/plain;charset=US-ASCII", d.mime_type());
+/// assert_eq!(b"A brief note", d.data());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataURL {
+    base64: bool,
+    data: Vec<u8>,
+    media_type: String,
+    params: Vec<(String, String)>,
+}
+
+impl DataURL {
+    /// from_url reads the content of u, whose scheme must be "data". The query,
+    /// if any, is part of the body, while the fragment is ignored.
+    pub fn from_url(u: &URL) -> Result<Self, Error> {
+        if u.scheme != "data" {
+            let err = format!("net/url: scheme {:?} is not data", u.scheme);
+            return Err(errors::new_misc(err));
+        }
+
+        if u.opaque.is_empty() {
+            return Err(errors::new_misc("net/url: data URL without opaque content"));
+        }
+
+        let mut content = u.opaque.clone();
+        if u.force_query || !u.raw_query.is_empty() {
+            content.push('?');
+            content.push_str(&u.raw_query);
+        }
+
+        Self::from_content(&content)
+    }
+
+    /// builder returns a builder of a `data:` URL carrying data, as by
+    /// [DataURLBuilder::new](struct.DataURLBuilder.html#method.new).
+    pub fn builder<T>(data: T) -> DataURLBuilder
+    where
+        T: Into<Vec<u8>>,
+    {
+        DataURLBuilder::new(data)
+    }
+
+    /// data returns the decoded body.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// into_data returns the decoded body, consuming the DataURL.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// is_base64 reports whether the body was encoded in base64.
+    pub fn is_base64(&self) -> bool {
+        self.base64
+    }
+
+    /// media_type returns the lowercased media type without parameters, such
+    /// as "image/png".
+    pub fn media_type(&self) -> &str {
+        &self.media_type
+    }
+
+    /// mime_type returns the media type followed by its parameters, such as
+    /// "text/plain;charset=utf-8", with the values unescaped.
+    pub fn mime_type(&self) -> String {
+        let mut out = self.media_type.clone();
+        for (k, v) in &self.params {
+            out.push(';');
+            out.push_str(k);
+            out.push('=');
+            out.push_str(v);
+        }
+        out
+    }
+
+    /// param returns the value of the first parameter named name, which is
+    /// matched case-insensitively.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// params returns the parameters of the media type in order, with the
+    /// names lowercased.
+    pub fn params(&self) -> &[(String, String)] {
+        &self.params
+    }
+
+    /// to_url returns the `data:` URL of the content, encoding the body in
+    /// base64 if it was.
+    pub fn to_url(&self) -> URL {
+        URL {
+            scheme: "data".to_string(),
+            opaque: self.to_string()["data:".len()..].to_string(),
+            ..URL::default()
+        }
+    }
+
+    /// from_content reads the part of a `data:` URL following the scheme.
+    fn from_content(content: &str) -> Result<Self, Error> {
+        let comma = content
+            .find(',')
+            .ok_or_else(|| errors::new_misc("net/url: missing comma in data URL"))?;
+        let (header, body) = (&content[..comma], &content[(comma + 1)..]);
+
+        let mut params = header.split(';');
+        let media_type = params.next().unwrap_or_default();
+
+        let mut out = DataURL {
+            base64: false,
+            data: vec![],
+            media_type: "text/plain".to_string(),
+            params: vec![],
+        };
+
+        if !media_type.is_empty() {
+            out.media_type =
+                internal::unescape_lossy(media_type, Encoding::PathSegment)?.to_ascii_lowercase();
+            check_media_type(&out.media_type)?;
+        }
+
+        let mut params = params.peekable();
+        while let Some(param) = params.next() {
+            if params.peek().is_none() && param.eq_ignore_ascii_case("base64") {
+                out.base64 = true;
+                break;
+            }
+
+            let (k, v) = match param.find('=') {
+                Some(i) => (&param[..i], &param[(i + 1)..]),
+                None => {
+                    let err = format!("net/url: invalid data URL parameter {:?}", param);
+                    return Err(errors::new_misc(err));
+                }
+            };
+
+            let k = internal::unescape_lossy(k, Encoding::PathSegment)?.to_ascii_lowercase();
+            if !is_token(&k) {
+                let err = format!("net/url: invalid data URL parameter name {:?}", k);
+                return Err(errors::new_misc(err));
+            }
+
+            let v = internal::unescape_lossy(v, Encoding::PathSegment)?.into_owned();
+            out.params.push((k, v));
+        }
+
+        if media_type.is_empty() && out.params.is_empty() {
+            out.params
+                .push(("charset".to_string(), "US-ASCII".to_string()));
+        }
+
+        let body = internal::unescape_bytes(body.as_bytes(), Encoding::PathSegment)?;
+        out.data = if out.base64 {
+            base64::decode(&body).map_err(|i| {
+                let err = format!("net/url: invalid base64 data in data URL at offset {}", i);
+                errors::new_misc(err)
+            })?
+        } else {
+            body.into_owned()
+        };
+
+        Ok(out)
+    }
+}
+
+impl fmt::Display for DataURL {
+    /// fmt writes the `data:` URL of the content, with the parameter values
+    /// and the body escaped as needed.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("data:")?;
+        internal::escape_into(self.media_type.as_bytes(), Encoding::Path, f)?;
+        for (k, v) in &self.params {
+            f.write_str(";")?;
+            internal::escape_into(k.as_bytes(), Encoding::Path, f)?;
+            f.write_str("=")?;
+            internal::escape_into(v.as_bytes(), Encoding::PathSegment, f)?;
+        }
+
+        if self.base64 {
+            write!(f, ";base64,{}", base64::encode(&self.data))
+        } else {
+            f.write_str(",")?;
+            internal::escape_into(&self.data, Encoding::Path, f)
+        }
+    }
+}
+
+impl FromStr for DataURL {
+    type Err = Error;
+
+    /// from_str parses a `data:` URL, as by [parse](fn.parse.html) followed by
+    /// [from_url](#method.from_url).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let u = super::parse(s)?;
+        Self::from_url(&u)
+    }
+}
+
+/// DataURLBuilder assembles a `data:` URL from bytes and a MIME type.
+///
+/// Unless told otherwise by [base64](#method.base64), the body is encoded in
+/// base64 only if that is shorter than %-escaping it. The URL returned by
+/// [build](#method.build) reads back to the same content.
+///
+/// # Example
+///
+/// ```
+/// use net::url::DataURL;
+///
+/// let u = DataURL::builder(&b"\x89PNG\r\n"[..])
+///     .mime_type("image/png")
+///     .base64(true)
+///     .build()
+///     .unwrap();
+/// assert_eq!("data:image/png;base64,iVBORw0K", u.to_string());
+///
+/// let u = DataURL::builder("¡hola!")
+///     .mime_type("text/plain; charset=utf-8")
+///     .build()
+///     .unwrap();
+/// assert_eq!("data:text/plain;charset=utf-8,%C2%A1hola%21", u.to_string());
+/// assert_eq!(b"\xc2\xa1hola!", DataURL::from_url(&u).unwrap().data());
+///
+/// assert!(DataURL::builder("").mime_type("text").build().is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct DataURLBuilder {
+    base64: Option<bool>,
+    data: Vec<u8>,
+    mime_type: Option<String>,
+    params: Vec<(String, String)>,
+}
+
+impl DataURLBuilder {
+    /// new returns a builder of a `data:` URL carrying data, with no media
+    /// type, which stands for "text/plain;charset=US-ASCII".
+    pub fn new<T>(data: T) -> Self
+    where
+        T: Into<Vec<u8>>,
+    {
+        Self {
+            base64: None,
+            data: data.into(),
+            mime_type: None,
+            params: vec![],
+        }
+    }
+
+    /// base64 tells whether the body is encoded in base64 rather than
+    /// %-escaped.
+    pub fn base64(mut self, base64: bool) -> Self {
+        self.base64 = Some(base64);
+        self
+    }
+
+    /// build validates the MIME type and parameters and assembles the URL.
+    pub fn build(&self) -> Result<URL, Error> {
+        let mut out = DataURL {
+            base64: false,
+            data: vec![],
+            media_type: String::new(),
+            params: vec![],
+        };
+
+        if let Some(v) = &self.mime_type {
+            let mut params = split_params(v);
+            out.media_type = params
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase();
+            check_media_type(&out.media_type)?;
+
+            for param in params.map(str::trim).filter(|v| !v.is_empty()) {
+                let (k, v) = match param.find('=') {
+                    Some(i) => (&param[..i], &param[(i + 1)..]),
+                    None => {
+                        let err = format!("net/url: invalid MIME type parameter {:?}", param);
+                        return Err(errors::new_misc(err));
+                    }
+                };
+                out.params
+                    .push((k.trim().to_string(), unquote(v.trim()).to_string()));
+            }
+        }
+
+        for (k, v) in &self.params {
+            out.params.push((k.clone(), v.clone()));
+        }
+
+        for (k, _) in out.params.iter_mut() {
+            if !is_token(k) {
+                let err = format!("net/url: invalid MIME type parameter name {:?}", k);
+                return Err(errors::new_misc(err));
+            }
+            k.make_ascii_lowercase();
+        }
+
+        if out.media_type.is_empty() && !out.params.is_empty() {
+            out.media_type = "text/plain".to_string();
+        }
+
+        out.base64 = match self.base64 {
+            Some(v) => v,
+            None => {
+                let escaped = self
+                    .data
+                    .iter()
+                    .map(|&c| {
+                        if internal::should_escape(c, Encoding::Path) {
+                            3
+                        } else {
+                            1
+                        }
+                    })
+                    .sum::<usize>();
+                ";base64".len() + base64::encoded_len(self.data.len()) < escaped
+            }
+        };
+        out.data = self.data.clone();
+
+        Ok(out.to_url())
+    }
+
+    /// mime_type sets the MIME type, such as "image/png" or
+    /// "text/plain; charset=utf-8", whose parameter values may be quoted.
+    pub fn mime_type<T>(mut self, mime_type: T) -> Self
+    where
+        T: ToString,
+    {
+        self.mime_type = Some(mime_type.to_string());
+        self
+    }
+
+    /// param adds a parameter to the MIME type, after those given by
+    /// [mime_type](#method.mime_type).
+    pub fn param<K, V>(mut self, name: K, value: V) -> Self
+    where
+        K: ToString,
+        V: ToString,
+    {
+        self.params.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// check_media_type reports an error unless media_type is of the form
+/// type "/" subtype, both being tokens per RFC 2045.
+fn check_media_type(media_type: &str) -> Result<(), Error> {
+    match media_type.find('/') {
+        Some(i) if is_token(&media_type[..i]) && is_token(&media_type[(i + 1)..]) => Ok(()),
+        _ => {
+            let err = format!("net/url: invalid media type {:?}", media_type);
+            Err(errors::new_misc(err))
+        }
+    }
+}
+
+/// is_token reports whether s is a non-empty token per RFC 2045, Section 5.1:
+///     token := 1*<any (US-ASCII) CHAR except SPACE, CTLs, or tspecials>
+fn is_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|c| c.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?=".contains(&c))
+}
+
+/// split_params splits a MIME type on the semicolons which aren't within a
+/// quoted-string.
+fn split_params(mime_type: &str) -> impl Iterator<Item = &str> {
+    let (mut quoted, mut escaped) = (false, false);
+    mime_type.split(move |c| {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ';' if !quoted => return true,
+            _ => {}
+        }
+        false
+    })
+}
+
+/// unquote strips the double quotes around a quoted-string and its
+/// backslash escapes, returning other values as they are.
+fn unquote(s: &str) -> Cow<'_, str> {
+    match s.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(v) if v.contains('\\') => {
+            let mut out = String::with_capacity(v.len());
+            let mut chars = v.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => out.extend(chars.next()),
+                    c => out.push(c),
+                }
+            }
+            Cow::Owned(out)
+        }
+        Some(v) => Cow::Borrowed(v),
+        None => Cow::Borrowed(s),
+    }
+}
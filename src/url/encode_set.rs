@@ -0,0 +1,187 @@
+use std::borrow::Cow;
+
+use super::errors::Error;
+use super::internal::{self, Encoding};
+
+/// EncodeSet is the set of bytes to percent-encode, along with whether a space
+/// is written as '+'. Sets used by this package are provided as constants, and
+/// others can be derived from them by adding or removing bytes.
+///
+/// Bytes which aren't ASCII are always in the set, so that escaping yields a
+/// valid string.
+///
+/// # Example
+///
+/// ```
+/// use net::url::{self, EncodeSet};
+///
+/// // RFC 5987 attr-char, as for filenames in Content-Disposition headers.
+/// const ATTR_CHAR: EncodeSet = EncodeSet::UNRESERVED.remove_all(b"!#$&+^`|");
+///
+/// assert_eq!("na%C3%AFve%20%25%20rate+1.txt", url::escape_with("naïve % rate+1.txt", &ATTR_CHAR));
+///
+/// // The canonical URI of AWS signature version 4 keeps slashes.
+/// let aws = EncodeSet::UNRESERVED.remove(b'/');
+/// assert_eq!("/photos/my%20cat%2A.jpg", url::escape_with("/photos/my cat*.jpg", &aws));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EncodeSet {
+    bits: [u64; 4],
+    space_as_plus: bool,
+}
+
+impl EncodeSet {
+    /// FRAGMENT is the set escaped in fragments.
+    pub const FRAGMENT: EncodeSet = EncodeSet::from_mode(Encoding::Fragment);
+    /// HOST is the set escaped in hosts.
+    pub const HOST: EncodeSet = EncodeSet::from_mode(Encoding::Host);
+    /// NON_ASCII holds the bytes which aren't ASCII only, which are in every
+    /// set.
+    pub const NON_ASCII: EncodeSet = EncodeSet {
+        bits: [0, 0, !0, !0],
+        space_as_plus: false,
+    };
+    /// PATH is the set escaped in whole paths, as by
+    /// [escaped_path](struct.URL.html#method.escaped_path).
+    pub const PATH: EncodeSet = EncodeSet::from_mode(Encoding::Path);
+    /// PATH_SEGMENT is the set escaped by [path_escape](fn.path_escape.html).
+    pub const PATH_SEGMENT: EncodeSet = EncodeSet::from_mode(Encoding::PathSegment);
+    /// QUERY_COMPONENT is the set escaped by
+    /// [query_escape](fn.query_escape.html), which writes a space as '+'.
+    pub const QUERY_COMPONENT: EncodeSet = EncodeSet::from_mode(Encoding::QueryComponent);
+    /// UNRESERVED escapes all but the unreserved characters of RFC 3986,
+    /// ALPHA / DIGIT / "-" / "." / "_" / "~", as required by OAuth 1.0.
+    pub const UNRESERVED: EncodeSet = EncodeSet {
+        bits: [!0, !0, !0, !0],
+        space_as_plus: false,
+    }
+    .remove_range(b'0', b'9')
+    .remove_range(b'A', b'Z')
+    .remove_range(b'a', b'z')
+    .remove_all(b"-._~");
+    /// USER_PASSWORD is the set escaped in the username and password of
+    /// userinfo.
+    pub const USER_PASSWORD: EncodeSet = EncodeSet::from_mode(Encoding::UserPassword);
+    /// ZONE is the set escaped in the zone identifiers of IPv6 hosts.
+    pub const ZONE: EncodeSet = EncodeSet::from_mode(Encoding::Zone);
+
+    /// add returns the set with c added.
+    pub const fn add(mut self, c: u8) -> Self {
+        self.bits[(c >> 6) as usize] |= 1 << (c & 63);
+        self
+    }
+
+    /// add_all returns the set with all bytes of s added.
+    pub const fn add_all(mut self, s: &[u8]) -> Self {
+        let mut i = 0;
+        while i < s.len() {
+            self = self.add(s[i]);
+            i += 1;
+        }
+
+        self
+    }
+
+    /// contains reports whether c is escaped.
+    #[inline]
+    pub const fn contains(&self, c: u8) -> bool {
+        self.bits[(c >> 6) as usize] & (1 << (c & 63)) != 0
+    }
+
+    /// keeps_unreserved reports whether none of the unreserved characters of
+    /// RFC 3986 is escaped, which lets them be skipped a word at a time.
+    pub(crate) const fn keeps_unreserved(&self) -> bool {
+        let unreserved = EncodeSet::UNRESERVED.bits;
+        self.bits[0] & !unreserved[0] == 0 && self.bits[1] & !unreserved[1] == 0
+    }
+
+    /// is_space_as_plus reports whether a space is written as '+' rather than
+    /// "%20", and a '+' read back as a space.
+    pub const fn is_space_as_plus(&self) -> bool {
+        self.space_as_plus
+    }
+
+    /// remove returns the set with c removed, unless c isn't ASCII.
+    pub const fn remove(mut self, c: u8) -> Self {
+        if c.is_ascii() {
+            self.bits[(c >> 6) as usize] &= !(1 << (c & 63));
+        }
+        self
+    }
+
+    /// remove_all returns the set with all bytes of s removed.
+    pub const fn remove_all(mut self, s: &[u8]) -> Self {
+        let mut i = 0;
+        while i < s.len() {
+            self = self.remove(s[i]);
+            i += 1;
+        }
+
+        self
+    }
+
+    /// space_as_plus returns the set writing a space as '+' if enabled, which
+    /// only takes effect when the space is in the set.
+    pub const fn space_as_plus(mut self, enabled: bool) -> Self {
+        self.space_as_plus = enabled;
+        self
+    }
+
+    const fn from_mode(mode: Encoding) -> Self {
+        let mut out = EncodeSet {
+            bits: [0; 4],
+            space_as_plus: std::matches!(mode, Encoding::QueryComponent),
+        };
+
+        let mut c = 0;
+        while c < 256 {
+            if internal::escapes(c as u8, mode) {
+                out = out.add(c as u8);
+            }
+            c += 1;
+        }
+
+        out
+    }
+
+    const fn remove_range(mut self, first: u8, last: u8) -> Self {
+        let mut c = first;
+        while c <= last {
+            self = self.remove(c);
+            c += 1;
+        }
+
+        self
+    }
+}
+
+/// escape_with escapes the bytes of s in set, borrowing s if there is nothing
+/// to escape.
+pub fn escape_with<'a>(s: &'a str, set: &EncodeSet) -> Cow<'a, str> {
+    internal::escape_with(s, set)
+}
+
+/// unescape_with does the inverse transformation of escape_with, decoding
+/// every escape whatever the set, and '+' into a space if the set writes a
+/// space that way. It returns an error if any % is not followed by two
+/// hexadecimal digits, or if the decoded bytes aren't valid UTF-8.
+///
+/// # Example
+///
+/// ```
+/// use net::url::{self, EncodeSet};
+///
+/// assert_eq!("a+b c", url::unescape_with("a+b%20c", &EncodeSet::UNRESERVED).unwrap());
+/// assert_eq!("a b c", url::unescape_with("a+b%20c", &EncodeSet::QUERY_COMPONENT).unwrap());
+/// assert!(url::unescape_with("%zz", &EncodeSet::UNRESERVED).is_err());
+/// ```
+pub fn unescape_with<'a>(s: &'a str, set: &EncodeSet) -> Result<Cow<'a, str>, Error> {
+    // Neither mode restricts the escapes, as hosts do.
+    let mode = if set.is_space_as_plus() {
+        Encoding::QueryComponent
+    } else {
+        Encoding::PathSegment
+    };
+
+    internal::unescape(s, mode)
+}
@@ -1,26 +1,243 @@
 //! module errors define errors about URL operations
 //!
 
+use std::fmt;
+
 use thiserror::Error;
 
 /// Error define errors about URL operations
+///
+/// Errors are `Send + Sync + 'static`, so they can cross threads and be
+/// boxed into the error types of applications. The cause of a Wrapped error
+/// is available from [source](#method.source).
+///
+/// # Example
+///
+/// ```
+/// use std::error::Error as _;
+///
+/// let err = net::url::parse("http://[::1").unwrap_err();
+///
+/// assert_eq!("parse http://[::1: missing ']' in host", err.to_string());
+/// assert_eq!("missing ']' in host", err.source().unwrap().to_string());
+///
+/// let err: Box<dyn std::error::Error + Send + Sync> = err.into();
+/// assert!(err.downcast_ref::<net::url::errors::Error>().is_some());
+/// ```
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("invalid URL escape `{0}`")]
     Escape(String),
     #[error("invalid character `{0}` in host name")]
     InvalidHost(String),
+    /// InvalidUTF8 reports an escaped string whose unescaped bytes aren't
+    /// valid UTF-8.
+    #[error("invalid UTF-8 in unescaped `{0}`")]
+    InvalidUTF8(String),
+    /// Limit reports an input exceeding one of the [Limits](../struct.Limits.html)
+    /// or [NestedQueryLimits](../struct.NestedQueryLimits.html).
+    #[error("{0}")]
+    Limit(LimitExceeded),
     #[error("{0}")]
     Misc(String),
+    /// Parse reports a malformed URL along with the position of the problem.
+    #[error("{0}")]
+    Parse(ParseError),
     /// Wrapped reports an error and the operation and URL that caused it.
     #[error("{op} {url}: {err}")]
     Wrapped {
         op: String,
         url: String,
-        err: Box<dyn std::error::Error>,
+        #[source]
+        err: Box<dyn std::error::Error + Send + Sync + 'static>,
     },
 }
 
+impl Error {
+    /// parse_error returns the ParseError behind the error, if any, looking
+    /// through a Wrapped error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use net::url::{self, errors::ParseErrorKind};
+    ///
+    /// let err = url::parse("http://example.com:8o/").unwrap_err();
+    ///
+    /// let err = err.parse_error().unwrap();
+    /// assert_eq!(ParseErrorKind::InvalidPort(":8o".to_string()), err.kind);
+    /// assert_eq!(18, err.offset);
+    /// ```
+    pub fn parse_error(&self) -> Option<&ParseError> {
+        match self {
+            Error::Parse(err) => Some(err),
+            Error::Wrapped { err, .. } => err.downcast_ref::<Error>()?.parse_error(),
+            _ => None,
+        }
+    }
+}
+
+/// LimitExceeded tells which of the [Limits](../struct.Limits.html) or
+/// [NestedQueryLimits](../struct.NestedQueryLimits.html) an input exceeds, along
+/// with the limit.
+#[derive(Error, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LimitExceeded {
+    #[error("net/url: decoded query longer than {0} bytes")]
+    DecodedLength(usize),
+    #[error("net/url: host longer than {0} bytes")]
+    HostLength(usize),
+    #[error("net/url: query key {key:?} nested deeper than {limit}")]
+    KeyDepth { key: String, limit: usize },
+    #[error("net/url: host with more than {0} labels")]
+    Labels(usize),
+    #[error("net/url: more than {0} query parameters")]
+    QueryPairs(usize),
+    #[error("net/url: URL longer than {0} bytes")]
+    URLLength(usize),
+    #[error("net/url: more than {limit} values for query key {key:?}")]
+    ValuesPerKey { key: String, limit: usize },
+}
+
+impl From<LimitExceeded> for Error {
+    fn from(err: LimitExceeded) -> Self {
+        Error::Limit(err)
+    }
+}
+
+/// ParseErrorKind tells what is wrong in a malformed URL.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ParseErrorKind {
+    /// a relative path has a colon in its first segment, which would read as
+    /// a scheme
+    ColonInFirstSegment,
+    /// an ASCII control character is found
+    ControlCharacter,
+    /// an empty URL is given for a request
+    EmptyURL,
+    /// a malformed or forbidden %-escape
+    InvalidEscape(String),
+    /// a character not allowed in a host name
+    InvalidHostCharacter(String),
+    /// a non-numeric port, with its leading colon
+    InvalidPort(String),
+    /// a URL of a request is neither absolute nor an absolute path
+    InvalidRequestURI,
+    /// %-escapes whose unescaped bytes aren't valid UTF-8
+    InvalidUTF8(String),
+    /// a character not allowed in the userinfo part
+    InvalidUserinfo,
+    /// an IP literal of a host lacks its closing ']'
+    MissingBracket,
+    /// a URL starts with a colon instead of a scheme
+    MissingScheme,
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::ColonInFirstSegment => {
+                write!(f, "first path segment in URL cannot contain colon")
+            }
+            ParseErrorKind::ControlCharacter => {
+                write!(f, "net/url: invalid control character in URL")
+            }
+            ParseErrorKind::EmptyURL => write!(f, "empty url"),
+            ParseErrorKind::InvalidEscape(v) => write!(f, "invalid URL escape `{}`", v),
+            ParseErrorKind::InvalidHostCharacter(v) => {
+                write!(f, "invalid character `{}` in host name", v)
+            }
+            ParseErrorKind::InvalidPort(v) => write!(f, "invalid port {} after host", v),
+            ParseErrorKind::InvalidRequestURI => write!(f, "invalid URI for request"),
+            ParseErrorKind::InvalidUTF8(v) => write!(f, "invalid UTF-8 in unescaped `{}`", v),
+            ParseErrorKind::InvalidUserinfo => write!(f, "net/url: invalid userinfo"),
+            ParseErrorKind::MissingBracket => write!(f, "missing ']' in host"),
+            ParseErrorKind::MissingScheme => write!(f, "missing protocol scheme"),
+        }
+    }
+}
+
+/// ParseError reports what is wrong in a malformed URL and where.
+#[derive(Error, Debug, Clone, PartialEq, Eq, Hash)]
+#[error("{kind}")]
+pub struct ParseError {
+    /// what is wrong
+    pub kind: ParseErrorKind,
+    /// byte offset of the problem into the input
+    pub offset: usize,
+    /// the malformed input
+    pub input: String,
+}
+
+impl ParseError {
+    pub(crate) fn new(kind: ParseErrorKind, offset: usize) -> Self {
+        Self {
+            kind,
+            offset,
+            input: String::new(),
+        }
+    }
+
+    /// render returns the input with a caret under the offending position on
+    /// the next line, followed by the description of the error. Control
+    /// characters of the input are shown as U+FFFD to keep the caret aligned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let err = net::url::parse("http://a b.com/").unwrap_err();
+    ///
+    /// assert_eq!(
+    ///     "http://a b.com/\n        ^ invalid character ` ` in host name",
+    ///     err.parse_error().unwrap().render()
+    /// );
+    /// ```
+    pub fn render(&self) -> String {
+        let input = self
+            .input
+            .chars()
+            .map(|c| if c.is_control() { '\u{fffd}' } else { c })
+            .collect::<String>();
+        let column = match self.input.get(..self.offset) {
+            Some(v) => v.chars().count(),
+            None => self.input.chars().count(),
+        };
+
+        format!("{}\n{}^ {}", input, " ".repeat(column), self.kind)
+    }
+
+    /// at shifts the offset of an error found in a slice starting at the given
+    /// offset of the input.
+    pub(crate) fn at(mut self, offset: usize) -> Self {
+        self.offset += offset;
+        self
+    }
+
+    /// with_input sets the input the offset refers to.
+    pub(crate) fn with_input(mut self, input: &str) -> Self {
+        self.input = input.to_string();
+        self
+    }
+
+    /// from_escape converts an error of unescaping found at the given offset.
+    /// Errors without a kind of their own are reported as InvalidEscape.
+    pub(crate) fn from_escape(offset: usize, err: Error) -> Self {
+        let kind = match err {
+            Error::Escape(v) => ParseErrorKind::InvalidEscape(v),
+            Error::InvalidHost(v) => ParseErrorKind::InvalidHostCharacter(v),
+            Error::InvalidUTF8(v) => ParseErrorKind::InvalidUTF8(v),
+            err => ParseErrorKind::InvalidEscape(err.to_string()),
+        };
+
+        Self::new(kind, offset)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        Error::Parse(err)
+    }
+}
+
 pub fn new_misc<T>(desc: T) -> Error
 where
     T: ToString,
@@ -0,0 +1,342 @@
+use std::collections::hash_map;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::vec;
+
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+
+use super::super::errors::{self, Error};
+use super::super::Values;
+
+/// Deserializer reads a struct or a map from the values of a query.
+pub(super) struct Deserializer {
+    values: Values,
+}
+
+/// MapAccess yields the keys of a query along with their values.
+struct MapAccess {
+    entries: hash_map::IntoIter<String, Vec<String>>,
+    value: Option<(String, Vec<String>)>,
+}
+
+/// SeqAccess yields the values of a repeated key.
+struct SeqAccess {
+    key: String,
+    values: vec::IntoIter<String>,
+}
+
+/// ValueDeserializer reads the values given for a key.
+struct ValueDeserializer {
+    key: String,
+    values: Vec<String>,
+}
+
+impl Deserializer {
+    pub(super) fn new(values: Values) -> Self {
+        Self { values }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(MapAccess {
+            entries: self.values.0.into_iter(),
+            value: None,
+        })
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.entries.next() {
+            Some((k, v)) => {
+                let de: de::value::StrDeserializer<Error> = k.as_str().into_deserializer();
+                let key = seed.deserialize(de)?;
+                self.value = Some((k, v));
+                Ok(Some(key))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let (key, values) = self
+            .value
+            .take()
+            .ok_or_else(|| errors::new_misc("net/url: value requested before key"))?;
+
+        seed.deserialize(ValueDeserializer { key, values })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for SeqAccess {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.values.next() {
+            Some(v) => seed
+                .deserialize(ValueDeserializer {
+                    key: self.key.clone(),
+                    values: vec![v],
+                })
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.values.len())
+    }
+}
+
+impl ValueDeserializer {
+    /// parse parses the single value of the key.
+    fn parse<T>(self) -> Result<T, Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let key = self.key.clone();
+        let v = self.single()?;
+        v.parse::<T>().map_err(|err| {
+            let err = format!("net/url: invalid value {:?} for key {:?}: {}", v, key, err);
+            errors::new_misc(err)
+        })
+    }
+
+    /// single returns the value of the key, reporting an error if it's repeated.
+    fn single(mut self) -> Result<String, Error> {
+        if self.values.len() != 1 {
+            let err = format!(
+                "net/url: expected a single value for key {:?}, got {}",
+                self.key,
+                self.values.len()
+            );
+            return Err(errors::new_misc(err));
+        }
+
+        Ok(self.values.pop().unwrap())
+    }
+
+    fn unsupported(&self, kind: &str) -> Error {
+        errors::new_misc(format!(
+            "net/url: unsupported {} for key {:?}",
+            kind, self.key
+        ))
+    }
+}
+
+macro_rules! deserialize_parse {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+            where
+                V: Visitor<'de>,
+            {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.values.len() == 1 {
+            visitor.visit_string(self.single()?)
+        } else {
+            self.deserialize_seq(visitor)
+        }
+    }
+
+    deserialize_parse! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_string(self.single()?)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_string(self.single()?)
+    }
+
+    fn deserialize_bytes<V>(self, _visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(self.unsupported("bytes"))
+    }
+
+    fn deserialize_byte_buf<V>(self, _visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(self.unsupported("bytes"))
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(SeqAccess {
+            key: self.key,
+            values: self.values.into_iter(),
+        })
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V>(self, _visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(self.unsupported("nested map"))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(self.unsupported("nested struct"))
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let v: de::value::StringDeserializer<Error> = self.single()?.into_deserializer();
+        visitor.visit_enum(v)
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+}
@@ -0,0 +1,129 @@
+//! Serde support of the "application/x-www-form-urlencoded" format, mapping
+//! the fields of structs and entries of maps to the keys of a query string.
+//!
+//! Sequences are written as repeated keys, `None` leaves its key out, unit
+//! enum variants are written by name, and the other values in their string
+//! form. Nested structs and maps have no representation in the format and are
+//! rejected.
+
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::errors::{self, Error};
+
+mod de;
+mod ser;
+
+/// from_query_str deserializes a value, typically a struct or a map, from the
+/// URL-encoded query string, as parsed by [parse_query](fn.parse_query.html).
+/// A key may be repeated to fill a sequence, and optional fields can be left
+/// out.
+///
+/// # Example
+///
+/// ```
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, PartialEq, Deserialize)]
+/// #[serde(rename_all = "lowercase")]
+/// enum Order {
+///     Asc,
+///     Desc,
+/// }
+///
+/// #[derive(Debug, PartialEq, Deserialize)]
+/// struct Search {
+///     q: String,
+///     page: u32,
+///     tag: Vec<String>,
+///     order: Option<Order>,
+/// }
+///
+/// let got: Search = net::url::from_query_str("q=rust+url&page=2&tag=a&tag=b").unwrap();
+/// let expect = Search {
+///     q: "rust url".to_string(),
+///     page: 2,
+///     tag: vec!["a".to_string(), "b".to_string()],
+///     order: None,
+/// };
+/// assert_eq!(expect, got);
+///
+/// let err = net::url::from_query_str::<Search>("q=x&page=two&tag=a").unwrap_err();
+/// assert_eq!(
+///     r#"net/url: invalid value "two" for key "page": invalid digit found in string"#,
+///     err.to_string()
+/// );
+/// ```
+pub fn from_query_str<T>(query: &str) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let values = super::parse_query(query).map_err(|(_, err)| err)?;
+    T::deserialize(de::Deserializer::new(values))
+}
+
+/// to_query_string serializes a value, which must be a struct or a map, into
+/// a URL-encoded query string, keeping the order of the fields.
+///
+/// # Example
+///
+/// ```
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// #[serde(rename_all = "lowercase")]
+/// enum Order {
+///     Asc,
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Search<'a> {
+///     q: &'a str,
+///     page: u32,
+///     tag: Vec<&'a str>,
+///     order: Option<Order>,
+///     limit: Option<u32>,
+/// }
+///
+/// let s = Search {
+///     q: "rust url",
+///     page: 2,
+///     tag: vec!["a", "b&c"],
+///     order: Some(Order::Asc),
+///     limit: None,
+/// };
+///
+/// assert_eq!(
+///     "q=rust+url&page=2&tag=a&tag=b%26c&order=asc",
+///     net::url::to_query_string(&s).unwrap()
+/// );
+/// ```
+pub fn to_query_string<T>(value: &T) -> Result<String, Error>
+where
+    T: Serialize + ?Sized,
+{
+    let mut out = super::OrderedValues::default();
+    value.serialize(ser::Serializer::new(&mut out))?;
+
+    Ok(out.encode())
+}
+
+impl serde::de::Error for Error {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        errors::new_misc(format!("net/url: {}", msg))
+    }
+}
+
+impl serde::ser::Error for Error {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        errors::new_misc(format!("net/url: {}", msg))
+    }
+}
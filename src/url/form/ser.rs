@@ -0,0 +1,510 @@
+use serde::ser::{self, Impossible, Serialize};
+
+use super::super::errors::{self, Error};
+use super::super::OrderedValues;
+
+/// Serializer writes a struct or a map as the key-value pairs of a query.
+pub(super) struct Serializer<'a> {
+    out: &'a mut OrderedValues,
+}
+
+/// MapSerializer writes the entries of a map, whose keys must serialize as a
+/// single value.
+pub(super) struct MapSerializer<'a> {
+    key: Option<String>,
+    out: &'a mut OrderedValues,
+}
+
+/// SeqSerializer writes the elements of a sequence as repeated keys.
+pub(super) struct SeqSerializer<'a> {
+    key: &'a str,
+    out: &'a mut OrderedValues,
+}
+
+/// StructSerializer writes the fields of a struct.
+pub(super) struct StructSerializer<'a> {
+    out: &'a mut OrderedValues,
+}
+
+/// ValueSerializer writes a value for the given key.
+struct ValueSerializer<'a> {
+    key: &'a str,
+    out: &'a mut OrderedValues,
+}
+
+impl<'a> Serializer<'a> {
+    pub(super) fn new(out: &'a mut OrderedValues) -> Self {
+        Self { out }
+    }
+}
+
+fn top_level(kind: &str) -> Error {
+    let err = format!(
+        "net/url: top-level value must be a struct or a map, got {}",
+        kind
+    );
+    errors::new_misc(err)
+}
+
+fn unsupported(kind: &str, key: &str) -> Error {
+    errors::new_misc(format!("net/url: unsupported {} for key {:?}", kind, key))
+}
+
+impl<'a> ser::Serializer for Serializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = MapSerializer<'a>;
+    type SerializeStruct = StructSerializer<'a>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    fn serialize_bool(self, _v: bool) -> Result<(), Error> {
+        Err(top_level("bool"))
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<(), Error> {
+        Err(top_level("integer"))
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<(), Error> {
+        Err(top_level("integer"))
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<(), Error> {
+        Err(top_level("integer"))
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<(), Error> {
+        Err(top_level("integer"))
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<(), Error> {
+        Err(top_level("integer"))
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<(), Error> {
+        Err(top_level("integer"))
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<(), Error> {
+        Err(top_level("integer"))
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<(), Error> {
+        Err(top_level("integer"))
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<(), Error> {
+        Err(top_level("float"))
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<(), Error> {
+        Err(top_level("float"))
+    }
+
+    fn serialize_char(self, _v: char) -> Result<(), Error> {
+        Err(top_level("char"))
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<(), Error> {
+        Err(top_level("string"))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<(), Error> {
+        Err(top_level("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        Err(top_level("option"))
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(top_level("option"))
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Err(top_level("unit"))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        Err(top_level("unit struct"))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), Error> {
+        Err(top_level("enum"))
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(top_level("enum"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(top_level("sequence"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(top_level("tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(top_level("tuple struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(top_level("enum"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Ok(MapSerializer {
+            key: None,
+            out: self.out,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        Ok(StructSerializer { out: self.out })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(top_level("enum"))
+    }
+}
+
+impl<'a> ser::SerializeMap for MapSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let mut v = OrderedValues::default();
+        key.serialize(ValueSerializer {
+            key: "",
+            out: &mut v,
+        })?;
+
+        match v.0.pop() {
+            Some((_, k)) if v.0.is_empty() => {
+                self.key = Some(k);
+                Ok(())
+            }
+            _ => Err(errors::new_misc("net/url: map key must be a single value")),
+        }
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| errors::new_misc("net/url: map value without key"))?;
+
+        value.serialize(ValueSerializer {
+            key: &key,
+            out: &mut *self.out,
+        })
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeSeq for SeqSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(ValueSerializer {
+            key: self.key,
+            out: &mut *self.out,
+        })
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeTuple for SeqSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for SeqSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeStruct for StructSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(ValueSerializer {
+            key,
+            out: &mut *self.out,
+        })
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ValueSerializer<'a> {
+    fn add<T>(self, value: T) -> Result<(), Error>
+    where
+        T: ToString,
+    {
+        self.out.add(self.key, value);
+        Ok(())
+    }
+}
+
+impl<'a> ser::Serializer for ValueSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = SeqSerializer<'a>;
+    type SerializeTuple = SeqSerializer<'a>;
+    type SerializeTupleStruct = SeqSerializer<'a>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Impossible<(), Error>;
+    type SerializeStruct = Impossible<(), Error>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        self.add(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Error> {
+        self.add(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Error> {
+        self.add(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Error> {
+        self.add(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Error> {
+        self.add(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Error> {
+        self.add(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Error> {
+        self.add(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Error> {
+        self.add(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Error> {
+        self.add(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), Error> {
+        self.add(v)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Error> {
+        self.add(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        self.add(v)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        self.add(v)
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<(), Error> {
+        Err(unsupported("bytes", self.key))
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        self.add("")
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        self.add("")
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        self.add(variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(unsupported("enum variant with data", self.key))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Ok(SeqSerializer {
+            key: self.key,
+            out: self.out,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(unsupported("enum variant with data", self.key))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(unsupported("nested map", self.key))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        Err(unsupported("nested struct", self.key))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(unsupported("enum variant with data", self.key))
+    }
+}
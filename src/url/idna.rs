@@ -0,0 +1,348 @@
+//! IDNA processing per UTS #46, converting internationalized domain names
+//! between their Unicode form and the ASCII-compatible "xn--" form resolvable
+//! through DNS.
+//!
+//! The mapping step is implemented in-crate and covers case folding, full-width
+//! forms, ideographic full stops and default ignorable characters, followed by
+//! normalization to NFC. The Bidi and ContextJ rules are not checked.
+
+use super::errors::{self, Error};
+use super::nfc::nfc;
+
+const ACE_PREFIX: &str = "xn--";
+
+// Bootstring parameters for Punycode, see RFC 3492 §5.
+const BASE: u32 = 36;
+const TMIN: u32 = 1;
+const TMAX: u32 = 26;
+const SKEW: u32 = 38;
+const DAMP: u32 = 700;
+const INITIAL_BIAS: u32 = 72;
+const INITIAL_N: u32 = 0x80;
+
+/// domain_to_ascii converts a domain name to its ASCII form, applying the UTS #46
+/// ToASCII operation: labels are mapped, and those with non-ASCII characters are
+/// encoded with Punycode behind the "xn--" prefix. The result is checked against
+/// the hyphen rules, the STD3 rules allowing only letters, digits and hyphens in
+/// labels, and DNS length limits.
+///
+/// # Example
+///
+/// ```
+/// use net::url;
+///
+/// assert_eq!("xn--bcher-kva.example", url::domain_to_ascii("Bücher.example").unwrap());
+/// assert_eq!("xn--bcher-kva.example", url::domain_to_ascii("Bu\u{308}cher.example").unwrap());
+/// assert!(url::domain_to_ascii("xn--a.example").is_err());
+/// assert!(url::domain_to_ascii("a b.example").is_err());
+/// ```
+pub fn domain_to_ascii(domain: &str) -> Result<String, Error> {
+    to_ascii(domain, true)
+}
+
+/// domain_to_unicode converts a domain name to its Unicode form, applying the
+/// UTS #46 ToUnicode operation: labels are mapped, and those starting with "xn--"
+/// are decoded from Punycode.
+///
+/// # Example
+///
+/// ```
+/// use net::url;
+///
+/// assert_eq!("bücher.example", url::domain_to_unicode("xn--bcher-kva.EXAMPLE").unwrap());
+/// ```
+pub fn domain_to_unicode(domain: &str) -> Result<String, Error> {
+    let labels = map(domain)?
+        .split('.')
+        .map(|v| match v.strip_prefix(ACE_PREFIX) {
+            Some(encoded) => decode_label(v, encoded),
+            None => Ok(v.to_string()),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(labels.join("."))
+}
+
+/// to_ascii implements ToASCII. With strict set, labels must obey CheckHyphens
+/// and UseSTD3ASCIIRules, and the whole domain VerifyDnsLength.
+pub(crate) fn to_ascii(domain: &str, strict: bool) -> Result<String, Error> {
+    let mapped = map(domain)?;
+
+    let mut labels = Vec::new();
+    for label in mapped.split('.') {
+        let unicode = match label.strip_prefix(ACE_PREFIX) {
+            Some(encoded) => decode_label(label, encoded)?,
+            None => label.to_string(),
+        };
+
+        if strict && !valid_hyphens(&unicode) {
+            let err = format!("net/url: invalid hyphens in IDNA label {:?}", label);
+            return Err(errors::new_misc(err));
+        }
+
+        if strict {
+            if let Some(c) = unicode.chars().find(|&c| !valid_std3(c)) {
+                let err = format!(
+                    "net/url: invalid character {:?} in IDNA label {:?}",
+                    c, label
+                );
+                return Err(errors::new_misc(err));
+            }
+        }
+
+        if label.is_ascii() {
+            labels.push(label.to_string());
+        } else {
+            let encoded = punycode_encode(label).ok_or_else(|| overflow(label))?;
+            labels.push(format!("{}{}", ACE_PREFIX, encoded));
+        }
+    }
+
+    let out = labels.join(".");
+    if strict {
+        verify_dns_length(&out, &labels)?;
+    }
+
+    Ok(out)
+}
+
+/// punycode_decode decodes a Punycode string per RFC 3492 §6.2. It returns None
+/// on malformed input or overflow.
+pub(crate) fn punycode_decode(input: &str) -> Option<String> {
+    if !input.is_ascii() {
+        return None;
+    }
+
+    let (basic, extended) = match input.rfind('-') {
+        Some(i) => (&input[..i], &input[(i + 1)..]),
+        None => ("", input),
+    };
+
+    let mut out = basic.chars().collect::<Vec<_>>();
+    let (mut n, mut i, mut bias) = (INITIAL_N, 0u32, INITIAL_BIAS);
+
+    let mut digits = extended.bytes().peekable();
+    while digits.peek().is_some() {
+        let old_i = i;
+        let mut w = 1u32;
+        let mut k = BASE;
+        loop {
+            let digit = decode_digit(digits.next()?)?;
+            i = i.checked_add(digit.checked_mul(w)?)?;
+
+            let t = threshold(k, bias);
+            if digit < t {
+                break;
+            }
+
+            w = w.checked_mul(BASE - t)?;
+            k += BASE;
+        }
+
+        let len = out.len() as u32 + 1;
+        bias = adapt(i - old_i, len, old_i == 0);
+        n = n.checked_add(i / len)?;
+        i %= len;
+
+        out.insert(i as usize, std::char::from_u32(n)?);
+        i += 1;
+    }
+
+    Some(out.into_iter().collect())
+}
+
+/// punycode_encode encodes a string with Punycode per RFC 3492 §6.3. It returns
+/// None on overflow.
+pub(crate) fn punycode_encode(input: &str) -> Option<String> {
+    let input = input.chars().map(|c| c as u32).collect::<Vec<_>>();
+
+    let mut out = input
+        .iter()
+        .filter(|&&c| c < INITIAL_N)
+        .map(|&c| c as u8 as char)
+        .collect::<String>();
+
+    let basic = out.len() as u32;
+    if basic > 0 {
+        out.push('-');
+    }
+
+    let (mut n, mut delta, mut bias) = (INITIAL_N, 0u32, INITIAL_BIAS);
+    let mut h = basic;
+    while (h as usize) < input.len() {
+        let m = *input.iter().filter(|&&c| c >= n).min()?;
+        delta = delta.checked_add((m - n).checked_mul(h + 1)?)?;
+        n = m;
+
+        for &c in &input {
+            if c < n {
+                delta = delta.checked_add(1)?;
+            }
+
+            if c != n {
+                continue;
+            }
+
+            let mut q = delta;
+            let mut k = BASE;
+            loop {
+                let t = threshold(k, bias);
+                if q < t {
+                    break;
+                }
+
+                out.push(encode_digit(t + (q - t) % (BASE - t)));
+                q = (q - t) / (BASE - t);
+                k += BASE;
+            }
+
+            out.push(encode_digit(q));
+            bias = adapt(delta, h + 1, h == basic);
+            delta = 0;
+            h += 1;
+        }
+
+        delta = delta.checked_add(1)?;
+        n = n.checked_add(1)?;
+    }
+
+    Some(out)
+}
+
+/// adapt is the bias adaptation function of RFC 3492 §6.1.
+fn adapt(delta: u32, num_points: u32, first_time: bool) -> u32 {
+    let mut delta = if first_time { delta / DAMP } else { delta / 2 };
+    delta += delta / num_points;
+
+    let mut k = 0;
+    while delta > ((BASE - TMIN) * TMAX) / 2 {
+        delta /= BASE - TMIN;
+        k += BASE;
+    }
+
+    k + (((BASE - TMIN + 1) * delta) / (delta + SKEW))
+}
+
+/// decode_label decodes the Punycode part of an "xn--" label and checks that it
+/// is the canonical encoding of a non-ASCII label.
+fn decode_label(label: &str, encoded: &str) -> Result<String, Error> {
+    let err = || errors::new_misc(format!("net/url: invalid punycode label {:?}", label));
+
+    let decoded = punycode_decode(encoded).ok_or_else(err)?;
+    if decoded.is_ascii() || map(&decoded)? != decoded {
+        return Err(err());
+    }
+
+    match punycode_encode(&decoded) {
+        Some(v) if v == encoded => Ok(decoded),
+        _ => Err(err()),
+    }
+}
+
+fn decode_digit(c: u8) -> Option<u32> {
+    match c {
+        b'a'..=b'z' => Some((c - b'a') as u32),
+        b'A'..=b'Z' => Some((c - b'A') as u32),
+        b'0'..=b'9' => Some((c - b'0') as u32 + 26),
+        _ => None,
+    }
+}
+
+fn encode_digit(d: u32) -> char {
+    if d < 26 {
+        (b'a' + d as u8) as char
+    } else {
+        (b'0' + (d - 26) as u8) as char
+    }
+}
+
+/// map applies the UTS #46 mapping step to the whole domain, with label
+/// separators mapped to '.', and normalizes the result to NFC.
+fn map(domain: &str) -> Result<String, Error> {
+    let mut out = String::with_capacity(domain.len());
+    for c in domain.chars() {
+        match c {
+            'A'..='Z' => out.push(c.to_ascii_lowercase()),
+            _ if c.is_ascii_control() || ('\u{80}'..='\u{9f}').contains(&c) => {
+                let err = format!("net/url: invalid character {:?} in domain", c);
+                return Err(errors::new_misc(err));
+            }
+            _ if c.is_ascii() => out.push(c),
+            // ideographic and full-width full stops
+            '\u{3002}' | '\u{ff0e}' | '\u{ff61}' => out.push('.'),
+            // full-width ASCII variants
+            '\u{ff01}'..='\u{ff5e}' => {
+                let v = std::char::from_u32(c as u32 - 0xfee0).unwrap();
+                out.push(v.to_ascii_lowercase());
+            }
+            // default ignorable code points mapped to nothing
+            '\u{ad}'
+            | '\u{34f}'
+            | '\u{180b}'..='\u{180d}'
+            | '\u{200b}'
+            | '\u{2060}'
+            | '\u{fe00}'..='\u{fe0f}'
+            | '\u{feff}' => {}
+            _ if c.is_whitespace() || c == '\u{fffd}' || is_noncharacter(c) => {
+                let err = format!("net/url: invalid character {:?} in domain", c);
+                return Err(errors::new_misc(err));
+            }
+            _ => out.extend(c.to_lowercase()),
+        }
+    }
+
+    if out.is_ascii() {
+        return Ok(out);
+    }
+
+    Ok(nfc(&out))
+}
+
+/// is_noncharacter reports whether c is one of the code points permanently
+/// reserved for internal use, which UTS #46 disallows.
+fn is_noncharacter(c: char) -> bool {
+    ('\u{fdd0}'..='\u{fdef}').contains(&c) || (c as u32) & 0xfffe == 0xfffe
+}
+
+fn overflow(label: &str) -> Error {
+    errors::new_misc(format!("net/url: punycode overflow in label {:?}", label))
+}
+
+fn threshold(k: u32, bias: u32) -> u32 {
+    if k <= bias {
+        TMIN
+    } else if k >= bias + TMAX {
+        TMAX
+    } else {
+        k - bias
+    }
+}
+
+/// valid_hyphens implements CheckHyphens: a label can neither begin nor end
+/// with a hyphen, nor have hyphens in both its third and fourth positions.
+fn valid_hyphens(label: &str) -> bool {
+    !label.starts_with('-') && !label.ends_with('-') && label.get(2..4) != Some("--")
+}
+
+/// valid_std3 reports whether c is allowed in a label by UseSTD3ASCIIRules,
+/// which leaves lower-case letters, digits and hyphens as the only ASCII
+/// characters of a mapped label.
+fn valid_std3(c: char) -> bool {
+    !c.is_ascii() || c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'
+}
+
+/// verify_dns_length implements VerifyDnsLength, where a trailing empty label
+/// stands for the root.
+fn verify_dns_length(domain: &str, labels: &[String]) -> Result<(), Error> {
+    let (labels, len) = match labels.split_last() {
+        Some((last, rest)) if last.is_empty() && !rest.is_empty() => (rest, domain.len() - 1),
+        _ => (labels, domain.len()),
+    };
+
+    if len > 253 || labels.iter().any(|v| v.is_empty() || v.len() > 63) {
+        let err = format!("net/url: invalid DNS length of domain {:?}", domain);
+        return Err(errors::new_misc(err));
+    }
+
+    Ok(())
+}
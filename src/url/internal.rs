@@ -1,12 +1,23 @@
+use std::borrow::Cow;
+use std::convert::TryInto;
+use std::fmt;
+
 use super::errors::Error;
+use super::EncodeSet;
 
 const WHITESPACE: u8 = ' ' as u8;
 const PERCENT: u8 = '%' as u8;
 const PLUS: u8 = '+' as u8;
 
+const ASCII: &str = "bytes left unescaped are ASCII";
 const UPPERHEX: &'static str = "0123456789ABCDEF";
 const PERCENT_IPV6: &[u8] = "%25".as_bytes();
 
+// Lane masks of the words classified by unreserved.
+const ONES: u64 = 0x0101_0101_0101_0101;
+const HIGH_BITS: u64 = 0x8080_8080_8080_8080;
+const LOW_BITS: u64 = 0x7f7f_7f7f_7f7f_7f7f;
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Encoding {
     Path,
@@ -18,137 +29,123 @@ pub enum Encoding {
     Fragment,
 }
 
-pub fn escape(s: &str, mode: Encoding) -> String {
-    let (space_count, hex_count) = {
-        let (mut space_count, mut hex_count) = (0, 0);
-
-        for &c in s.as_bytes() {
-            if !should_escape(c, mode) {
-                continue;
-            }
-
-            match mode {
-                Encoding::QueryComponent if c == WHITESPACE => space_count += 1,
-                _ => hex_count += 1,
-            }
+impl Encoding {
+    /// set returns the EncodeSet of the mode.
+    pub fn set(self) -> &'static EncodeSet {
+        match self {
+            Encoding::Path => &EncodeSet::PATH,
+            Encoding::PathSegment => &EncodeSet::PATH_SEGMENT,
+            Encoding::Host => &EncodeSet::HOST,
+            Encoding::Zone => &EncodeSet::ZONE,
+            Encoding::UserPassword => &EncodeSet::USER_PASSWORD,
+            Encoding::QueryComponent => &EncodeSet::QUERY_COMPONENT,
+            Encoding::Fragment => &EncodeSet::FRAGMENT,
         }
+    }
+}
 
-        (space_count, hex_count)
+/// escape escapes s per mode, borrowing it if there is nothing to escape.
+pub fn escape(s: &str, mode: Encoding) -> Cow<'_, str> {
+    escape_with(s, mode.set())
+}
+
+/// escape_with escapes the bytes of s in set, borrowing s if there is nothing
+/// to escape.
+pub fn escape_with<'a>(s: &'a str, set: &EncodeSet) -> Cow<'a, str> {
+    let (i, n) = match count_escapes(s.as_bytes(), set) {
+        Some(v) => v,
+        None => return Cow::Borrowed(s),
     };
 
-    if space_count == 0 && hex_count == 0 {
-        return s.to_string();
-    }
+    // Bytes before the first to escape are ASCII.
+    let mut t = String::with_capacity(s.len() + 2 * n);
+    t.push_str(&s[..i]);
+    let _ = escape_with_into(&s.as_bytes()[i..], set, &mut t);
 
-    if hex_count == 0 {
-        return s.replace(" ", "+");
-    }
+    Cow::Owned(t)
+}
+
+/// escape_bytes escapes the bytes s; bytes that aren't ASCII are always
+/// escaped, so the result is a valid string.
+pub fn escape_bytes(s: &[u8], mode: Encoding) -> String {
+    let mut t = String::with_capacity(s.len());
+    let _ = escape_into(s, mode, &mut t);
+
+    t
+}
 
+/// escape_into writes the escaped form of the bytes s to out.
+pub fn escape_into<W>(s: &[u8], mode: Encoding, out: &mut W) -> fmt::Result
+where
+    W: fmt::Write + ?Sized,
+{
+    escape_with_into(s, mode.set(), out)
+}
+
+/// escape_with_into writes the bytes s to out, escaping those in set. Runs of
+/// bytes left as they are are written at once.
+pub fn escape_with_into<W>(s: &[u8], set: &EncodeSet, out: &mut W) -> fmt::Result
+where
+    W: fmt::Write + ?Sized,
+{
     let upperhex = UPPERHEX.as_bytes();
 
-    let mut t = String::with_capacity(s.len() + 2 * hex_count);
-    for &c in s.as_bytes() {
-        if c == WHITESPACE && mode == Encoding::QueryComponent {
-            t.push('+');
-        } else if should_escape(c, mode) {
-            t.push('%');
-            t.push(upperhex[(c >> 4) as usize] as char);
-            t.push(upperhex[(c & 0x0f) as usize] as char);
+    let mut s = s;
+    while let Some(i) = find_escape(s, set) {
+        // Bytes not to escape are ASCII.
+        out.write_str(std::str::from_utf8(&s[..i]).expect(ASCII))?;
+
+        let c = s[i];
+        if c == WHITESPACE && set.is_space_as_plus() {
+            out.write_char('+')?;
         } else {
-            t.push(c as char);
+            out.write_char('%')?;
+            out.write_char(upperhex[(c >> 4) as usize] as char)?;
+            out.write_char(upperhex[(c & 0x0f) as usize] as char)?;
         }
+
+        s = &s[(i + 1)..];
     }
 
-    t
+    out.write_str(std::str::from_utf8(s).expect(ASCII))
 }
 
 /// unescape unescapes a string; the mode specifies
 /// which section of the URL string is being unescaped.
-pub fn unescape(s: &str, mode: Encoding) -> Result<String, Error> {
-    // Count %, check that they're well-formed.
-    let (n, has_plus) = {
-        let mut n = 0;
-        let mut has_plus = false;
-
-        let mut i = 0;
-        let s = s.as_bytes();
-        while i < s.len() {
-            let c = s[i];
-            match c {
-                PERCENT => {
-                    n += 1;
-                    if i + 2 >= s.len() || !is_hex(s[i + 1]) || !is_hex(s[i + 2]) {
-                        let err = if s[i..].len() < 3 {
-                            s[i..].to_vec()
-                        } else {
-                            s[i..=(i + 2)].to_vec()
-                        };
-
-                        return Err(Error::Escape(unsafe { String::from_utf8_unchecked(err) }));
-                    }
-
-                    // Per https://tools.ietf.org/html/rfc3986#page-21
-                    // in the host component %-encoding can only be used
-                    // for non-ASCII bytes.
-                    // But https://tools.ietf.org/html/rfc6874#section-2
-                    // introduces %25 being allowed to escape a percent sign
-                    // in IPv6 scoped-address literals. Yay.
-                    if mode == Encoding::Host
-                        && unhex(s[i + 1]) < 8
-                        && (&s[i..=(i + 2)] != PERCENT_IPV6)
-                    {
-                        let err = unsafe { String::from_utf8_unchecked(s[i..=(i + 2)].to_vec()) };
-                        return Err(Error::Escape(err));
-                    }
-
-                    if mode == Encoding::Zone {
-                        // RFC 6874 says basically "anything goes" for zone identifiers
-                        // and that even non-ASCII can be redundantly escaped,
-                        // but it seems prudent to restrict %-escaped bytes here to those
-                        // that are valid host name bytes in their unescaped form.
-                        // That is, you can use escaping in the zone identifier but not
-                        // to introduce bytes you couldn't just write directly.
-                        // But Windows puts spaces here! Yay.
-                        let v = unhex(s[i + 1]) << 4 | unhex(s[i + 2]);
-                        if (&s[i..=(i + 2)] != PERCENT_IPV6)
-                            && v != WHITESPACE
-                            && should_escape(v, Encoding::Host)
-                        {
-                            let err =
-                                unsafe { String::from_utf8_unchecked(s[i..=(i + 2)].to_vec()) };
-                            return Err(Error::Escape(err));
-                        }
-                    }
-
-                    i += 3;
-                }
-                PLUS => {
-                    has_plus = mode == Encoding::QueryComponent;
-                    i += 1;
-                }
-                _ => {
-                    if (mode == Encoding::Host || mode == Encoding::Zone)
-                        && (c < 0x80)
-                        && should_escape(c, mode)
-                    {
-                        return Err(Error::InvalidHost((c as char).to_string()));
-                    }
+/// The string is borrowed if there is nothing to unescape.
+/// It returns Error::InvalidUTF8 if the unescaped bytes aren't valid UTF-8.
+pub fn unescape(s: &str, mode: Encoding) -> Result<Cow<'_, str>, Error> {
+    match unescape_bytes(s.as_bytes(), mode)? {
+        Cow::Borrowed(_) => Ok(Cow::Borrowed(s)),
+        Cow::Owned(v) => String::from_utf8(v)
+            .map(Cow::Owned)
+            .map_err(|_| Error::InvalidUTF8(s.to_string())),
+    }
+}
 
-                    i += 1;
-                }
-            }
-        }
+/// unescape_lossy is like unescape, but replaces invalid UTF-8 sequences of
+/// the unescaped bytes with U+FFFD.
+pub fn unescape_lossy(s: &str, mode: Encoding) -> Result<Cow<'_, str>, Error> {
+    match unescape_bytes(s.as_bytes(), mode)? {
+        Cow::Borrowed(_) => Ok(Cow::Borrowed(s)),
+        Cow::Owned(v) => match String::from_utf8(v) {
+            Ok(v) => Ok(Cow::Owned(v)),
+            Err(err) => Ok(Cow::Owned(
+                String::from_utf8_lossy(err.as_bytes()).into_owned(),
+            )),
+        },
+    }
+}
 
-        (n, has_plus)
-    };
+/// unescape_bytes unescapes s into bytes, which is borrowed if there is
+/// nothing to unescape.
+pub fn unescape_bytes(s: &[u8], mode: Encoding) -> Result<Cow<'_, [u8]>, Error> {
+    let (n, has_plus) = check(s, mode).map_err(|(_, err)| err)?;
 
     if n == 0 && !has_plus {
-        return Ok(s.to_string());
+        return Ok(Cow::Borrowed(s));
     }
 
-    let s = s.as_bytes();
-
-    // String isn't ok in case of non-utf8 char
     let mut t = Vec::with_capacity(s.len() - 2 * n);
     let mut i = 0;
     while i < s.len() {
@@ -158,17 +155,96 @@ pub fn unescape(s: &str, mode: Encoding) -> Result<String, Error> {
                 t.push(c);
                 i += 2;
             }
-            PLUS if mode == Encoding::QueryComponent => t.push(' ' as u8),
-            PLUS => t.push('+' as u8),
+            PLUS if mode == Encoding::QueryComponent => t.push(b' '),
+            PLUS => t.push(b'+'),
             _ => t.push(s[i]),
         }
 
         i += 1;
     }
 
-    let t = unsafe { String::from_utf8_unchecked(t) };
+    Ok(Cow::Owned(t))
+}
 
-    Ok(t)
+/// validate reports an error if s can't be unescaped per mode, without
+/// unescaping it, along with the offset of the offending byte.
+pub fn validate(s: &str, mode: Encoding) -> Result<(), (usize, Error)> {
+    check(s.as_bytes(), mode).map(|_| ())
+}
+
+/// check counts the % escapes of s and reports whether it has a '+' to
+/// unescape, checking that they're well-formed.
+fn check(s: &[u8], mode: Encoding) -> Result<(usize, bool), (usize, Error)> {
+    let mut n = 0;
+    let mut has_plus = false;
+
+    let mut i = 0;
+    while i < s.len() {
+        let c = s[i];
+        match c {
+            PERCENT => {
+                n += 1;
+                if i + 2 >= s.len() || !is_hex(s[i + 1]) || !is_hex(s[i + 2]) {
+                    let end = s.len().min(i + 3);
+                    return Err((i, escape_error(&s[i..end])));
+                }
+
+                // Per https://tools.ietf.org/html/rfc3986#page-21
+                // in the host component %-encoding can only be used
+                // for non-ASCII bytes.
+                // But https://tools.ietf.org/html/rfc6874#section-2
+                // introduces %25 being allowed to escape a percent sign
+                // in IPv6 scoped-address literals. Yay.
+                if mode == Encoding::Host
+                    && unhex(s[i + 1]) < 8
+                    && (&s[i..=(i + 2)] != PERCENT_IPV6)
+                {
+                    return Err((i, escape_error(&s[i..=(i + 2)])));
+                }
+
+                if mode == Encoding::Zone {
+                    // RFC 6874 says basically "anything goes" for zone identifiers
+                    // and that even non-ASCII can be redundantly escaped,
+                    // but it seems prudent to restrict %-escaped bytes here to those
+                    // that are valid host name bytes in their unescaped form.
+                    // That is, you can use escaping in the zone identifier but not
+                    // to introduce bytes you couldn't just write directly.
+                    // But Windows puts spaces here! Yay.
+                    let v = unhex(s[i + 1]) << 4 | unhex(s[i + 2]);
+                    if (&s[i..=(i + 2)] != PERCENT_IPV6)
+                        && v != WHITESPACE
+                        && should_escape(v, Encoding::Host)
+                    {
+                        return Err((i, escape_error(&s[i..=(i + 2)])));
+                    }
+                }
+
+                i += 3;
+            }
+            PLUS => {
+                has_plus = mode == Encoding::QueryComponent;
+                i += 1;
+            }
+            _ => {
+                if (mode == Encoding::Host || mode == Encoding::Zone)
+                    && (c < 0x80)
+                    && should_escape(c, mode)
+                {
+                    return Err((i, Error::InvalidHost((c as char).to_string())));
+                }
+
+                i += 1;
+            }
+        }
+    }
+
+    Ok((n, has_plus))
+}
+
+/// escape_error reports the malformed escape s, which may cut a multi-byte
+/// character short.
+pub fn escape_error(s: &[u8]) -> Error {
+    Error::Escape(String::from_utf8_lossy(s).into_owned())
 }
 
 /// Return true if the specified character should be escaped when
@@ -176,14 +252,107 @@ pub fn unescape(s: &str, mode: Encoding) -> Result<String, Error> {
 ///
 /// Please be informed that for now should_escape does not check all
 /// reserved characters correctly. See golang.org/issue/5684.
+#[inline]
 pub(crate) fn should_escape(c: u8, mode: Encoding) -> bool {
+    mode.set().contains(c)
+}
+
+/// find_escape returns the index of the first byte of s to escape per set.
+pub fn find_escape(s: &[u8], set: &EncodeSet) -> Option<usize> {
+    let mut out = None;
+    visit_escapes(s, set, |i| {
+        out = Some(i);
+        false
+    });
+
+    out
+}
+
+/// count_escapes returns the index of the first byte of s to escape per set
+/// along with the number of bytes to escape, scanning s once.
+pub fn count_escapes(s: &[u8], set: &EncodeSet) -> Option<(usize, usize)> {
+    let mut out: Option<(usize, usize)> = None;
+    visit_escapes(s, set, |i| {
+        out.get_or_insert((i, 0)).1 += 1;
+        true
+    });
+
+    out
+}
+
+/// visit_escapes calls f with the index of each byte of s to escape per set,
+/// in order, until f returns false.
+///
+/// If the set keeps the unreserved characters, as all sets of this package
+/// do, s is classified 8 bytes at a time by unreserved, and only the bytes of
+/// a word which aren't unreserved are looked up in the set. Other sets, and
+/// the bytes past the last whole word, are looked up byte by byte.
+#[inline]
+fn visit_escapes<F>(s: &[u8], set: &EncodeSet, mut f: F)
+where
+    F: FnMut(usize) -> bool,
+{
+    let mut i = 0;
+    if set.keeps_unreserved() {
+        for chunk in s.chunks_exact(8) {
+            let word = u64::from_le_bytes(chunk.try_into().unwrap());
+
+            let mut others = !unreserved(word) & HIGH_BITS;
+            while others != 0 {
+                let j = (others.trailing_zeros() / 8) as usize;
+                if set.contains(chunk[j]) && !f(i + j) {
+                    return;
+                }
+                others &= others - 1;
+            }
+
+            i += 8;
+        }
+    }
+
+    for (j, &c) in s[i..].iter().enumerate() {
+        if set.contains(c) && !f(i + j) {
+            return;
+        }
+    }
+}
+
+/// unreserved returns the high bits of the bytes of word, in little-endian
+/// order, which are unreserved characters, ALPHA / DIGIT / "-" / "." / "_" /
+/// "~", classifying the 8 bytes at once.
+///
+/// The bytes are cleared of their high bit first, so that no sum carries into
+/// the next byte, and those which aren't ASCII are dropped at the end. A byte
+/// b is then at least c if b + (0x80 - c) has its high bit set, and equal to
+/// c if b ^ c is zero, which is when (b ^ c) + 0x7f doesn't have it set.
+#[inline]
+fn unreserved(word: u64) -> u64 {
+    let ascii = word & LOW_BITS;
+    let at_least = |v: u64, c: u8| v + ONES * (0x80 - c as u64);
+    let in_range = |v: u64, first: u8, last: u8| at_least(v, first) & !at_least(v, last + 1);
+    let equal = |v: u64, c: u8| {
+        let t = v ^ (ONES * c as u64);
+        !((t + LOW_BITS) | t)
+    };
+
+    // Setting bit 5 folds upper-case letters onto lower-case ones only.
+    let letters = in_range(ascii | (ONES * 0x20), b'a', b'z');
+    let digits = in_range(ascii, b'0', b'9');
+    let marks = in_range(ascii, b'-', b'.') | equal(ascii, b'_') | equal(ascii, b'~');
+
+    (letters | digits | marks) & !word & HIGH_BITS
+}
+
+/// escapes is the definition of should_escape, evaluated at compile time into
+/// the sets of EncodeSet.
+pub const fn escapes(c: u8, mode: Encoding) -> bool {
     let c = c as char;
     // §2.3 Unreserved characters (alphanum)
     if std::matches!(c, 'a'..='z' | 'A'..='Z' | '0'..='9') {
         return false;
     }
 
-    if mode == Encoding::Host || mode == Encoding::Zone {
+    if std::matches!(mode, Encoding::Host | Encoding::Zone) {
         // §3.2.2 Host allows
         //	sub-delims = "!" / "$" / "&" / "'" / "(" / ")" / "*" / "+" / "," / ";" / "="
         // as part of reg-name.
@@ -249,7 +418,7 @@ pub(crate) fn should_escape(c: u8, mode: Encoding) -> bool {
     // (1) we always escape sub-delims outside of the fragment, and (2) we always
     // escape single quote to avoid breaking callers that had previously assumed that
     // single quotes would be escaped. See issue #19917.
-    if mode == Encoding::Fragment && std::matches!(c, '!' | '(' | ')' | '*') {
+    if std::matches!(mode, Encoding::Fragment) && std::matches!(c, '!' | '(' | ')' | '*') {
         return false;
     }
 
@@ -257,12 +426,12 @@ pub(crate) fn should_escape(c: u8, mode: Encoding) -> bool {
     true
 }
 
-fn is_hex(c: u8) -> bool {
+pub fn is_hex(c: u8) -> bool {
     let c = c as char;
     std::matches!(c, '0'..='9'|'a'..='f'|'A'..='f')
 }
 
-fn unhex(c: u8) -> u8 {
+pub fn unhex(c: u8) -> u8 {
     let c = c as char;
     match c {
         '0'..='9' => (c as u8) - ('0' as u8),
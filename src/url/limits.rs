@@ -0,0 +1,72 @@
+use super::errors::{Error, LimitExceeded};
+
+/// Limits bounds the resources spent on parsing untrusted URLs and queries,
+/// as by [parse_with_limits](fn.parse_with_limits.html) and
+/// [parse_query_with](fn.parse_query_with.html). Exceeding a limit is reported
+/// as [Error::Limit](errors/enum.Error.html#variant.Limit), telling which
+/// limit it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Limits {
+    /// maximum length of a URL in bytes
+    pub max_url_len: usize,
+    /// maximum length in bytes of a host name, without port and brackets
+    pub max_host_len: usize,
+    /// maximum number of dot-separated labels of a host name
+    pub max_labels: usize,
+    /// maximum number of key-value pairs of a query
+    pub max_query_pairs: usize,
+    /// maximum number of values of a query key
+    pub max_values_per_key: usize,
+    /// maximum total length of the unescaped keys and values of a query
+    pub max_decoded_len: usize,
+}
+
+impl Limits {
+    /// NONE sets no limit at all.
+    pub const NONE: Limits = Limits {
+        max_url_len: usize::MAX,
+        max_host_len: usize::MAX,
+        max_labels: usize::MAX,
+        max_query_pairs: usize::MAX,
+        max_values_per_key: usize::MAX,
+        max_decoded_len: usize::MAX,
+    };
+
+    /// check_host reports an error if hostname is too long or has too many
+    /// labels.
+    pub(crate) fn check_host(&self, hostname: &str) -> Result<(), Error> {
+        if hostname.len() > self.max_host_len {
+            return Err(LimitExceeded::HostLength(self.max_host_len).into());
+        }
+
+        if hostname.split('.').count() > self.max_labels {
+            return Err(LimitExceeded::Labels(self.max_labels).into());
+        }
+
+        Ok(())
+    }
+
+    /// check_url reports an error if rawurl is too long.
+    pub(crate) fn check_url(&self, rawurl: &str) -> Result<(), Error> {
+        if rawurl.len() > self.max_url_len {
+            return Err(LimitExceeded::URLLength(self.max_url_len).into());
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Limits {
+    /// default allows URLs of 64 KiB with host names as long as DNS allows,
+    /// and queries of 1000 pairs with 100 values per key, decoding to 1 MiB.
+    fn default() -> Self {
+        Self {
+            max_url_len: 64 << 10,
+            max_host_len: 253,
+            max_labels: 127,
+            max_query_pairs: 1000,
+            max_values_per_key: 100,
+            max_decoded_len: 1 << 20,
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+use super::errors::{self, Error};
+use super::internal::{self, Encoding};
+use super::{EncodeSet, URL};
+
+/// TO is the set escaped in the addresses of a `mailto:` URL, which keeps the
+/// some-delims of RFC 6068 but the comma separating them.
+const TO: EncodeSet = EncodeSet::UNRESERVED.remove_all(b"!$'()*+;:@");
+
+/// HFIELD is the set escaped in the header names and values of a `mailto:`
+/// URL, which are made of the qchar of RFC 6068.
+const HFIELD: EncodeSet = EncodeSet::UNRESERVED.remove_all(b"!$'()*+,;:@");
+
+/// MailtoURL is the content of a `mailto:` URL as defined by RFC 6068: the
+/// recipients, the subject and body, and any other header fields, all
+/// unescaped.
+///
+/// Recipients are gathered from the addresses before the query and from the
+/// "to" header fields, and the "cc" and "bcc" header fields are split on
+/// commas. Header names are matched case-insensitively, and the fragment of
+/// the URL is ignored.
+///
+/// Line breaks in the body are written as "%0D%0A", as RFC 6068 requires,
+/// whether they are "\r\n", "\n" or "\r".
+///
+/// # Example
+///
+/// ```
+/// use net::url::{self, MailtoURL};
+///
+/// let u = url::parse("mailto:alice@example.com,bob@example.com?cc=carol@example.com&subject=Hello%20there&body=Hi!%0D%0ABye").unwrap();
+/// let m = MailtoURL::from_url(&u).unwrap();
+///
+/// assert_eq!(vec!["alice@example.com", "bob@example.com"], m.to);
+/// assert_eq!(vec!["carol@example.com"], m.cc);
+/// assert_eq!(Some("Hello there".to_string()), m.subject);
+/// assert_eq!(Some("Hi!\r\nBye".to_string()), m.body);
+///
+/// let m = MailtoURL {
+///     to: vec!["list@example.org".to_string()],
+///     subject: Some("a&b = c".to_string()),
+///     body: Some("line 1\nline 2".to_string()),
+///     headers: vec![("In-Reply-To".to_string(), "<3469A91.D10AF4C@example.com>".to_string())],
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     "mailto:list@example.org?subject=a%26b%20%3D%20c&body=line%201%0D%0Aline%202&In-Reply-To=%3C3469A91.D10AF4C@example.com%3E",
+///     m.to_string()
+/// );
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MailtoURL {
+    /// addresses of the primary recipients
+    pub to: Vec<String>,
+    /// addresses of the carbon copy recipients
+    pub cc: Vec<String>,
+    /// addresses of the blind carbon copy recipients
+    pub bcc: Vec<String>,
+    pub subject: Option<String>,
+    pub body: Option<String>,
+    /// other header fields, in order
+    pub headers: Vec<(String, String)>,
+}
+
+impl MailtoURL {
+    /// from_url reads the content of u, whose scheme must be "mailto".
+    pub fn from_url(u: &URL) -> Result<Self, Error> {
+        if u.scheme != "mailto" {
+            let err = format!("net/url: scheme {:?} is not mailto", u.scheme);
+            return Err(errors::new_misc(err));
+        }
+
+        if !u.host.is_empty() || u.user.is_some() || !u.path.is_empty() {
+            return Err(errors::new_misc(
+                "net/url: mailto URL with authority or absolute path",
+            ));
+        }
+
+        let mut out = MailtoURL::default();
+        push_addresses(&mut out.to, &u.opaque)?;
+
+        for hfield in u.raw_query.split('&').filter(|v| !v.is_empty()) {
+            let (name, value) = match hfield.find('=') {
+                Some(i) => (&hfield[..i], &hfield[(i + 1)..]),
+                None => {
+                    let err = format!("net/url: invalid mailto header field {:?}", hfield);
+                    return Err(errors::new_misc(err));
+                }
+            };
+
+            let name = unescape(name)?;
+            match name.to_ascii_lowercase().as_str() {
+                "to" => push_addresses(&mut out.to, value)?,
+                "cc" => push_addresses(&mut out.cc, value)?,
+                "bcc" => push_addresses(&mut out.bcc, value)?,
+                "subject" => out.subject = Some(unescape(value)?.into_owned()),
+                "body" => out.body = Some(unescape(value)?.into_owned()),
+                _ => out
+                    .headers
+                    .push((name.into_owned(), unescape(value)?.into_owned())),
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// header returns the value of the first of the other header fields named
+    /// name, which is matched case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// to_url returns the `mailto:` URL of the content.
+    pub fn to_url(&self) -> URL {
+        let mut opaque = String::new();
+        write_addresses(&mut opaque, &self.to, &TO).expect("writing to a String");
+
+        let mut raw_query = String::new();
+        self.write_hfields(&mut raw_query)
+            .expect("writing to a String");
+
+        URL {
+            scheme: "mailto".to_string(),
+            opaque,
+            raw_query,
+            ..URL::default()
+        }
+    }
+
+    /// write_hfields writes the header fields, cc and bcc first and then the
+    /// subject, the body and the others.
+    fn write_hfields<W>(&self, out: &mut W) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        let mut sep = "";
+        let mut hfield = |out: &mut W, name: &str| -> fmt::Result {
+            out.write_str(sep)?;
+            internal::escape_with_into(name.as_bytes(), &HFIELD, out)?;
+            sep = "&";
+            out.write_str("=")
+        };
+
+        if !self.cc.is_empty() {
+            hfield(out, "cc")?;
+            write_addresses(out, &self.cc, &HFIELD)?;
+        }
+        if !self.bcc.is_empty() {
+            hfield(out, "bcc")?;
+            write_addresses(out, &self.bcc, &HFIELD)?;
+        }
+        if let Some(v) = &self.subject {
+            hfield(out, "subject")?;
+            internal::escape_with_into(v.as_bytes(), &HFIELD, out)?;
+        }
+        if let Some(v) = &self.body {
+            hfield(out, "body")?;
+            internal::escape_with_into(crlf(v).as_bytes(), &HFIELD, out)?;
+        }
+        for (k, v) in &self.headers {
+            hfield(out, k)?;
+            internal::escape_with_into(v.as_bytes(), &HFIELD, out)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for MailtoURL {
+    /// fmt writes the `mailto:` URL of the content.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_url().fmt(f)
+    }
+}
+
+impl FromStr for MailtoURL {
+    type Err = Error;
+
+    /// from_str parses a `mailto:` URL, as by [parse](fn.parse.html) followed
+    /// by [from_url](#method.from_url).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let u = super::parse(s)?;
+        Self::from_url(&u)
+    }
+}
+
+/// crlf returns s with its line breaks written as "\r\n".
+fn crlf(s: &str) -> Cow<'_, str> {
+    if !s.contains(['\r', '\n']) {
+        return Cow::Borrowed(s);
+    }
+
+    let mut out = String::with_capacity(s.len() + 8);
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                out.push_str("\r\n");
+            }
+            '\n' => out.push_str("\r\n"),
+            c => out.push(c),
+        }
+    }
+
+    Cow::Owned(out)
+}
+
+/// push_addresses unescapes the comma-separated addresses of s into out,
+/// skipping empty ones.
+fn push_addresses(out: &mut Vec<String>, s: &str) -> Result<(), Error> {
+    for v in s.split(',').filter(|v| !v.is_empty()) {
+        out.push(unescape(v)?.into_owned());
+    }
+
+    Ok(())
+}
+
+/// unescape unescapes a part of a `mailto:` URL, where '+' stands for itself.
+fn unescape(s: &str) -> Result<Cow<'_, str>, Error> {
+    internal::unescape_lossy(s, Encoding::PathSegment)
+}
+
+/// write_addresses writes the addresses escaped by set, separated by commas.
+fn write_addresses<W>(out: &mut W, addresses: &[String], set: &EncodeSet) -> fmt::Result
+where
+    W: fmt::Write + ?Sized,
+{
+    for (i, v) in addresses.iter().enumerate() {
+        if i > 0 {
+            out.write_str(",")?;
+        }
+        internal::escape_with_into(v.as_bytes(), &set.add(b','), out)?;
+    }
+
+    Ok(())
+}
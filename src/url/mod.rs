@@ -5,21 +5,44 @@
 //! search old issues for history on decisions. Unit tests should also
 //! contain references to issue numbers with details.
 
+mod data;
+mod encode_set;
+mod form;
+mod idna;
+mod limits;
+mod mailto;
+mod nested;
+mod ordered_values;
 mod path;
 mod query;
+mod stream;
+mod template;
 mod url;
 mod user_info;
 mod values;
+mod whatwg;
 
 mod internal;
+mod nfc;
 
 pub mod errors;
 
+pub use data::*;
+pub use encode_set::*;
+pub use form::*;
+pub use idna::*;
+pub use limits::*;
+pub use mailto::*;
+pub use nested::*;
+pub use ordered_values::*;
 pub use path::*;
 pub use query::*;
+pub use stream::*;
+pub use template::*;
 pub use url::*;
 pub use user_info::*;
 pub use values::*;
+pub use whatwg::*;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,259 @@
+use std::collections::BTreeMap;
+
+use super::errors::{self, Error, LimitExceeded};
+use super::ParseQueryOptions;
+
+/// NestedQueryLimits bounds the work done by
+/// [parse_nested_query](fn.parse_nested_query.html) on untrusted input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NestedQueryLimits {
+    /// maximum number of bracketed segments after the name of a key, as 2 in
+    /// "a[b][]"
+    pub max_depth: usize,
+    /// maximum number of key-value pairs in the query
+    pub max_params: usize,
+}
+
+impl Default for NestedQueryLimits {
+    /// default allows keys as deep as "a[b][c][d][e][f]" and 1000 pairs.
+    fn default() -> Self {
+        Self {
+            max_depth: 5,
+            max_params: 1000,
+        }
+    }
+}
+
+/// QueryValue is a tree of query values, as denoted by the bracket notation of
+/// nested keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryValue {
+    /// values of keys ending with "[]", in order
+    Array(Vec<QueryValue>),
+    /// values of keys by the name of their next segment, as "b" in "a[b]"
+    Map(BTreeMap<String, QueryValue>),
+    /// a value
+    String(String),
+}
+
+impl QueryValue {
+    /// as_array returns the values of an array.
+    pub fn as_array(&self) -> Option<&[QueryValue]> {
+        match self {
+            QueryValue::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// as_map returns the entries of a map.
+    pub fn as_map(&self) -> Option<&BTreeMap<String, QueryValue>> {
+        match self {
+            QueryValue::Map(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// as_str returns the string of a value.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            QueryValue::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// encode encodes a map into "URL encoded" form with nested keys in
+    /// bracket notation ("a[b][]=1&a[c]=2"), sorted by key. Segments of keys
+    /// are escaped while the brackets are kept as they are. Empty arrays and
+    /// maps are left out.
+    pub fn encode(&self) -> String {
+        let mut out = String::new();
+        encode_into(&mut out, "", self);
+
+        out
+    }
+
+    /// get returns the value of key in a map.
+    pub fn get(&self, key: &str) -> Option<&QueryValue> {
+        self.as_map().and_then(|v| v.get(key))
+    }
+}
+
+/// parse_nested_query parses the URL-encoded query string into a map, the keys
+/// of which may be nested in bracket notation: "a[b]=1" sets the key "b" of
+/// the map named "a", and "a[]=1" appends to the array named "a". Each "[]"
+/// followed by a key starts a new map in the array unless its last map lacks
+/// that key, so that "a[][x]=1&a[][y]=2&a[][x]=3" makes two maps.
+///
+/// Keys are unescaped before looking for brackets, and keys with unbalanced
+/// brackets are taken as plain names. A repeated plain key keeps its last
+/// value. Unlike [parse_query](fn.parse_query.html), parse_nested_query stops
+/// at the first error, which may be a malformed escape, a key giving a value
+/// of another type than before, or a limit being exceeded, which is reported as
+/// an [Error::Limit](errors/enum.Error.html#variant.Limit).
+///
+/// # Example
+///
+/// ```
+/// use net::url::errors::{Error, LimitExceeded};
+/// use net::url::{self, NestedQueryLimits, QueryValue};
+///
+/// let q = "filter[status][]=open&filter[status][]=closed&filter[owner]=me&page=2";
+/// let v = url::parse_nested_query(q, &NestedQueryLimits::default()).unwrap();
+///
+/// let filter = v.get("filter").unwrap();
+/// let status = filter.get("status").unwrap().as_array().unwrap();
+/// assert_eq!(Some("closed"), status[1].as_str());
+/// assert_eq!(Some("me"), filter.get("owner").and_then(QueryValue::as_str));
+/// assert_eq!(Some("2"), v.get("page").and_then(QueryValue::as_str));
+///
+/// assert_eq!(
+///     "filter[owner]=me&filter[status][]=open&filter[status][]=closed&page=2",
+///     v.encode()
+/// );
+///
+/// let limits = NestedQueryLimits {
+///     max_depth: 1,
+///     ..Default::default()
+/// };
+/// match url::parse_nested_query(q, &limits) {
+///     Err(Error::Limit(LimitExceeded::KeyDepth { limit: 1, .. })) => {}
+///     v => panic!("unexpected {:?}", v),
+/// }
+/// ```
+pub fn parse_nested_query(query: &str, limits: &NestedQueryLimits) -> Result<QueryValue, Error> {
+    let mut out = QueryValue::Map(BTreeMap::new());
+
+    let options = ParseQueryOptions::compat();
+    for (i, kv) in super::values::query_pairs(query, &options).enumerate() {
+        if i == limits.max_params {
+            return Err(LimitExceeded::QueryPairs(limits.max_params).into());
+        }
+
+        let (key, value) = kv?;
+        let path = split_key(&key);
+        if path.len() - 1 > limits.max_depth {
+            let limit = limits.max_depth;
+            return Err(LimitExceeded::KeyDepth { key, limit }.into());
+        }
+
+        assign(&mut out, &path, value).map_err(|_| {
+            let err = format!(
+                "net/url: conflicting types of values for query key {:?}",
+                key
+            );
+            errors::new_misc(err)
+        })?;
+    }
+
+    Ok(out)
+}
+
+/// assign sets value at the given path under node, reporting an error if a
+/// node on the path has another type than the path implies.
+fn assign(node: &mut QueryValue, path: &[Option<&str>], value: String) -> Result<(), ()> {
+    match (path.split_first(), node) {
+        (None, QueryValue::String(v)) => *v = value,
+        (Some((Some(k), rest)), QueryValue::Map(m)) => {
+            let child = m.entry(k.to_string()).or_insert_with(|| new_node(rest));
+            assign(child, rest, value)?;
+        }
+        (Some((None, rest)), QueryValue::Array(a)) => {
+            let reuse = match (rest.first(), a.last()) {
+                (Some(Some(_)), Some(last @ QueryValue::Map(_))) => !has_path(last, rest),
+                _ => false,
+            };
+            if !reuse {
+                a.push(new_node(rest));
+            }
+            assign(a.last_mut().unwrap(), rest, value)?;
+        }
+        _ => return Err(()),
+    }
+
+    Ok(())
+}
+
+fn encode_into(out: &mut String, prefix: &str, value: &QueryValue) {
+    match value {
+        QueryValue::Array(a) => {
+            let prefix = format!("{}[]", prefix);
+            for v in a {
+                encode_into(out, &prefix, v);
+            }
+        }
+        QueryValue::Map(m) => {
+            for (k, v) in m {
+                let k = super::query_escape(k);
+                let prefix = if prefix.is_empty() {
+                    k.into_owned()
+                } else {
+                    format!("{}[{}]", prefix, k)
+                };
+                encode_into(out, &prefix, v);
+            }
+        }
+        QueryValue::String(v) => {
+            if !out.is_empty() {
+                out.push('&');
+            }
+            out.push_str(prefix);
+            out.push('=');
+            let _ = super::query_escape_into(v, out);
+        }
+    }
+}
+
+/// has_path reports whether the map keys of path lead to a value under node.
+/// Arrays can always take another value.
+fn has_path(node: &QueryValue, path: &[Option<&str>]) -> bool {
+    match (path.split_first(), node) {
+        (None, _) => true,
+        (Some((Some(k), rest)), QueryValue::Map(m)) => {
+            m.get(*k).map(|v| has_path(v, rest)).unwrap_or(false)
+        }
+        _ => false,
+    }
+}
+
+/// new_node returns an empty node of the type implied by the first segment of
+/// path.
+fn new_node(path: &[Option<&str>]) -> QueryValue {
+    match path.first() {
+        None => QueryValue::String(String::new()),
+        Some(Some(_)) => QueryValue::Map(BTreeMap::new()),
+        Some(None) => QueryValue::Array(Vec::new()),
+    }
+}
+
+/// split_key splits key into its name and bracketed segments, None standing
+/// for "[]". A key with unbalanced brackets or trailing characters after them
+/// is a plain name.
+fn split_key(key: &str) -> Vec<Option<&str>> {
+    let i = match key.find('[') {
+        Some(i) if i > 0 => i,
+        _ => return vec![Some(key)],
+    };
+
+    let mut out = vec![Some(&key[..i])];
+    let mut rest = &key[i..];
+    while !rest.is_empty() {
+        let end = match rest.find(']') {
+            Some(end) if rest.starts_with('[') => end,
+            _ => return vec![Some(key)],
+        };
+
+        let segment = &rest[1..end];
+        if segment.contains('[') {
+            return vec![Some(key)];
+        }
+
+        out.push(if segment.is_empty() {
+            None
+        } else {
+            Some(segment)
+        });
+        rest = &rest[(end + 1)..];
+    }
+
+    out
+}
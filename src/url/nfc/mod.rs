@@ -0,0 +1,165 @@
+//! Unicode Normalization Form C, per UAX #15, used by the IDNA mapping step.
+
+mod tables;
+
+use std::cmp::Ordering;
+
+use self::tables::{COMBINING_CLASSES, COMPOSITIONS, DECOMPOSITIONS};
+
+// Hangul syllable parameters, see the Unicode Standard §3.12.
+const S_BASE: u32 = 0xac00;
+const L_BASE: u32 = 0x1100;
+const V_BASE: u32 = 0x1161;
+const T_BASE: u32 = 0x11a7;
+const L_COUNT: u32 = 19;
+const V_COUNT: u32 = 21;
+const T_COUNT: u32 = 28;
+const N_COUNT: u32 = V_COUNT * T_COUNT;
+const S_COUNT: u32 = L_COUNT * N_COUNT;
+
+/// nfc returns s in Normalization Form C: canonically decomposed, with combining
+/// marks in canonical order, then recomposed.
+pub(crate) fn nfc(s: &str) -> String {
+    let mut chars = Vec::with_capacity(s.len());
+    for c in s.chars() {
+        decompose(c, &mut chars);
+    }
+
+    reorder(&mut chars);
+    compose(&mut chars);
+
+    chars.into_iter().collect()
+}
+
+/// decompose appends the full canonical decomposition of c to out.
+fn decompose(c: char, out: &mut Vec<char>) {
+    let v = c as u32;
+    if (S_BASE..S_BASE + S_COUNT).contains(&v) {
+        let s = v - S_BASE;
+        let t = s % T_COUNT;
+        out.push(from_u32(L_BASE + s / N_COUNT));
+        out.push(from_u32(V_BASE + (s % N_COUNT) / T_COUNT));
+        if t > 0 {
+            out.push(from_u32(T_BASE + t));
+        }
+        return;
+    }
+
+    match DECOMPOSITIONS.binary_search_by_key(&v, |&(c, _, _)| c) {
+        Ok(i) => {
+            let (_, first, second) = DECOMPOSITIONS[i];
+            decompose(from_u32(first), out);
+            if second != 0 {
+                decompose(from_u32(second), out);
+            }
+        }
+        Err(_) => out.push(c),
+    }
+}
+
+/// reorder sorts every run of non-starters by their combining class, keeping the
+/// order of those of the same class.
+fn reorder(chars: &mut [char]) {
+    let mut i = 0;
+    while i < chars.len() {
+        if combining_class(chars[i]) == 0 {
+            i += 1;
+            continue;
+        }
+
+        let n = chars[i..]
+            .iter()
+            .position(|&c| combining_class(c) == 0)
+            .unwrap_or(chars.len() - i);
+        chars[i..i + n].sort_by_key(|&c| combining_class(c));
+        i += n;
+    }
+}
+
+/// compose replaces in place each starter and the following characters it isn't
+/// blocked from by their primary composites.
+fn compose(chars: &mut Vec<char>) {
+    if chars.is_empty() {
+        return;
+    }
+
+    let mut starter = 0;
+    // A leading non-starter can't compose with what follows, so treat it as
+    // blocking everything.
+    let mut last_class = match combining_class(chars[0]) {
+        0 => 0,
+        _ => 256,
+    };
+
+    let mut n = 1;
+    for i in 1..chars.len() {
+        let c = chars[i];
+        let class = combining_class(c) as u16;
+
+        if last_class < class || last_class == 0 {
+            if let Some(v) = composite(chars[starter], c) {
+                chars[starter] = v;
+                continue;
+            }
+        }
+
+        if class == 0 {
+            starter = n;
+        }
+        last_class = class;
+        chars[n] = c;
+        n += 1;
+    }
+
+    chars.truncate(n);
+}
+
+/// composite returns the primary composite of a and b, if any.
+fn composite(a: char, b: char) -> Option<char> {
+    let (a, b) = (a as u32, b as u32);
+
+    if (L_BASE..L_BASE + L_COUNT).contains(&a) && (V_BASE..V_BASE + V_COUNT).contains(&b) {
+        let v = S_BASE + ((a - L_BASE) * V_COUNT + (b - V_BASE)) * T_COUNT;
+        return Some(from_u32(v));
+    }
+
+    // An LV syllable, which has no trailing consonant, composes with a T jamo.
+    if (S_BASE..S_BASE + S_COUNT).contains(&a) && (T_BASE + 1..T_BASE + T_COUNT).contains(&b) {
+        let t = (a - S_BASE) % T_COUNT;
+        if t == 0 {
+            return Some(from_u32(a + b - T_BASE));
+        }
+    }
+
+    COMPOSITIONS
+        .binary_search_by(|&(first, second, _)| (first, second).cmp(&(a, b)))
+        .ok()
+        .map(|i| from_u32(COMPOSITIONS[i].2))
+}
+
+/// combining_class returns the canonical combining class of c.
+fn combining_class(c: char) -> u8 {
+    let v = c as u32;
+    if v < 0x300 {
+        return 0;
+    }
+
+    let found = COMBINING_CLASSES.binary_search_by(|&(first, last, _)| {
+        if last < v {
+            Ordering::Less
+        } else if first > v {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
+        }
+    });
+
+    match found {
+        Ok(i) => COMBINING_CLASSES[i].2,
+        Err(_) => 0,
+    }
+}
+
+fn from_u32(v: u32) -> char {
+    std::char::from_u32(v).expect("code point from the normalization tables")
+}
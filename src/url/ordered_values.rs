@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use super::errors::{Error, LimitExceeded};
+use super::values::QueryBudget;
+use super::{ParseQueryOptions, Values};
+
+/// OrderedValues is like [Values](struct.Values.html) but keeps the key-value
+/// pairs in the order they were added, even when the keys are interleaved, so
+/// a query can be encoded again as it was parsed. The keys are case-sensitive.
+///
+/// # Example
+///
+/// ```
+/// use net::url::OrderedValues;
+///
+/// let mut v = OrderedValues::default();
+///
+/// v.add("name", "Ava");
+/// v.add("friend", "Jess");
+/// v.add("name", "Zoe");
+///
+/// assert_eq!("name=Ava&friend=Jess&name=Zoe", v.encode());
+/// assert_eq!(Some("Ava"), v.get("name"));
+/// assert_eq!(vec!["Ava", "Zoe"], v.get_all("name"));
+///
+/// v.set("name", "Mia");
+/// assert_eq!("name=Mia&friend=Jess", v.encode());
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct OrderedValues(pub Vec<(String, String)>);
+
+impl OrderedValues {
+    /// add appends the value for key after all the existing pairs.
+    pub fn add<K, V>(&mut self, key: K, value: V)
+    where
+        K: ToString,
+        V: ToString,
+    {
+        self.0.push((key.to_string(), value.to_string()));
+    }
+
+    /// del deletes the values associated with key.
+    pub fn del(&mut self, key: &str) {
+        self.0.retain(|(k, _)| k != key);
+    }
+
+    /// encode encodes the values into "URL encoded" form
+    /// ("foo=quux&bar=baz") in order.
+    pub fn encode(&self) -> String {
+        let mut out = String::new();
+        for (k, v) in &self.0 {
+            if !out.is_empty() {
+                out.push('&');
+            }
+
+            let _ = super::query_escape_into(k, &mut out);
+            out.push('=');
+            let _ = super::query_escape_into(v, &mut out);
+        }
+
+        out
+    }
+
+    /// get gets the first value associated with the given key.
+    /// If there are no values associated with the key, get returns
+    /// None.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// get_all gets all the values associated with the given key, in order.
+    pub fn get_all(&self, key: &str) -> Vec<&str> {
+        self.0
+            .iter()
+            .filter(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+            .collect()
+    }
+
+    /// set sets the key to value. It replaces any existing values, the new
+    /// pair taking the place of the first one of them.
+    pub fn set<K, V>(&mut self, key: K, value: V)
+    where
+        K: ToString,
+        V: ToString,
+    {
+        let key = key.to_string();
+        let value = value.to_string();
+
+        match self.0.iter().position(|(k, _)| *k == key) {
+            Some(i) => {
+                self.0[i].1 = value;
+
+                let mut j = 0;
+                self.0.retain(|(k, _)| {
+                    j += 1;
+                    j <= i + 1 || *k != key
+                });
+            }
+            None => self.0.push((key, value)),
+        }
+    }
+}
+
+impl From<OrderedValues> for Values {
+    fn from(v: OrderedValues) -> Self {
+        let mut out = Values::default();
+        for (k, v) in v.0 {
+            out.add(k, v);
+        }
+
+        out
+    }
+}
+
+/// parse_query_ordered is like [parse_query](fn.parse_query.html) but keeps the
+/// key-value pairs in the order found in the query.
+///
+/// # Example
+///
+/// ```
+/// let v = net::url::parse_query_ordered("b=2&a=1&b=3;c").unwrap();
+///
+/// let expected = vec![("b", "2"), ("a", "1"), ("b", "3"), ("c", "")]
+///     .into_iter()
+///     .map(|(k, v)| (k.to_string(), v.to_string()))
+///     .collect::<Vec<_>>();
+/// assert_eq!(expected, v.0);
+/// assert_eq!("b=2&a=1&b=3&c=", v.encode());
+/// ```
+pub fn parse_query_ordered(query: &str) -> Result<OrderedValues, (OrderedValues, Error)> {
+    parse_query_ordered_with(query, &ParseQueryOptions::compat())
+}
+
+/// parse_query_ordered_with is like [parse_query_with](fn.parse_query_with.html)
+/// but keeps the key-value pairs in the order found in the query.
+pub fn parse_query_ordered_with(
+    query: &str,
+    options: &ParseQueryOptions,
+) -> Result<OrderedValues, (OrderedValues, Error)> {
+    let mut err: Option<Error> = None;
+    let mut out = OrderedValues::default();
+
+    let limit = options.limits.max_values_per_key;
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut budget = QueryBudget::new(&options.limits);
+    for kv in super::values::query_pairs(query, options) {
+        if let Err(e) = budget.spend(&kv) {
+            return Err((out, e));
+        }
+
+        match kv {
+            Ok((k, v)) => {
+                if limit < usize::MAX {
+                    let n = counts.entry(k.clone()).or_default();
+                    if *n >= limit {
+                        return Err((out, LimitExceeded::ValuesPerKey { key: k, limit }.into()));
+                    }
+                    *n += 1;
+                }
+                out.0.push((k, v))
+            }
+            Err(e) if options.strict => return Err((out, e)),
+            Err(e) if err.is_none() => err = Some(e),
+            Err(_) => {}
+        }
+    }
+
+    match err {
+        Some(v) => Err((out, v)),
+        None => Ok(out),
+    }
+}
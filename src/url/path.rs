@@ -1,20 +1,100 @@
+use std::borrow::Cow;
+use std::fmt;
+
 use super::errors::Error;
 
 use super::internal::{self, Encoding};
 
 /// path_escape escapes the string so it can be safely placed inside a URL path segment,
 /// replacing special characters (including /) with %XX sequences as needed.
-pub fn path_escape(s: &str) -> String {
+/// The string is borrowed if there is nothing to escape.
+///
+/// # Example
+///
+/// ```
+/// use std::borrow::Cow;
+///
+/// assert_eq!("a%2Fb%20c", net::url::path_escape("a/b c"));
+/// assert!(std::matches!(net::url::path_escape("abc"), Cow::Borrowed(_)));
+/// ```
+pub fn path_escape(s: &str) -> Cow<'_, str> {
     internal::escape(s, Encoding::PathSegment)
 }
 
+/// path_escape_into writes the escaped form of s, as returned by path_escape, to
+/// out without allocating.
+///
+/// # Example
+///
+/// ```
+/// let mut out = String::from("x=");
+/// net::url::path_escape_into("a/b c", &mut out).unwrap();
+///
+/// assert_eq!("x=a%2Fb%20c", out);
+/// ```
+pub fn path_escape_into<W>(s: &str, out: &mut W) -> fmt::Result
+where
+    W: fmt::Write + ?Sized,
+{
+    internal::escape_into(s.as_bytes(), Encoding::PathSegment, out)
+}
+
+/// path_escape_bytes is like path_escape but escapes arbitrary bytes, which
+/// need not be valid UTF-8.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!("a%2Fb%FF", net::url::path_escape_bytes(b"a/b\xff"));
+/// ```
+pub fn path_escape_bytes(s: &[u8]) -> String {
+    internal::escape_bytes(s, Encoding::PathSegment)
+}
+
 /// path_unescape does the inverse transformation of path_escape,
 /// converting each 3-byte encoded substring of the form "%AB" into the
 /// hex-decoded byte 0xAB. It returns an error if any % is not followed
-/// by two hexadecimal digits.
+/// by two hexadecimal digits, or if the decoded bytes aren't valid UTF-8.
 ///
 /// path_unescape is identical to query_unescape except that it does not
 /// unescape '+' to ' ' (space).
+///
+/// # Example
+///
+/// ```
+/// use net::url::{self, errors::Error};
+///
+/// assert_eq!("a/b", url::path_unescape("a%2Fb").unwrap());
+///
+/// match url::path_unescape("a%FF") {
+///     Err(Error::InvalidUTF8(v)) => assert_eq!("a%FF", v),
+///     v => panic!("unexpected {:?}", v),
+/// }
+/// ```
 pub fn path_unescape(s: &str) -> Result<String, Error> {
-    internal::unescape(s, Encoding::PathSegment)
+    internal::unescape(s, Encoding::PathSegment).map(|v| v.into_owned())
+}
+
+/// path_unescape_bytes is like path_unescape but returns the decoded bytes,
+/// which need not be valid UTF-8.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(b"a\xff".to_vec(), net::url::path_unescape_bytes(b"a%FF").unwrap());
+/// ```
+pub fn path_unescape_bytes(s: &[u8]) -> Result<Vec<u8>, Error> {
+    internal::unescape_bytes(s, Encoding::PathSegment).map(|v| v.into_owned())
+}
+
+/// path_unescape_lossy is like path_unescape but replaces invalid UTF-8
+/// sequences of the decoded bytes with U+FFFD.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!("a\u{fffd}", net::url::path_unescape_lossy("a%FF").unwrap());
+/// ```
+pub fn path_unescape_lossy(s: &str) -> Result<String, Error> {
+    internal::unescape_lossy(s, Encoding::PathSegment).map(|v| v.into_owned())
 }
@@ -1,18 +1,85 @@
+use std::borrow::Cow;
+use std::fmt;
+
 use super::errors::Error;
 
 use super::internal::{self, Encoding};
 
 /// query_escape escapes the string so it can be safely placed
 /// inside a URL query.
-pub fn query_escape(s: &str) -> String {
+/// The string is borrowed if there is nothing to escape.
+///
+/// # Example
+///
+/// ```
+/// use std::borrow::Cow;
+///
+/// assert_eq!("a%26b+c", net::url::query_escape("a&b c"));
+/// assert!(std::matches!(net::url::query_escape("abc"), Cow::Borrowed(_)));
+/// ```
+pub fn query_escape(s: &str) -> Cow<'_, str> {
     internal::escape(s, Encoding::QueryComponent)
 }
 
+/// query_escape_into writes the escaped form of s, as returned by query_escape, to
+/// out without allocating.
+///
+/// # Example
+///
+/// ```
+/// let mut out = String::from("x=");
+/// net::url::query_escape_into("a&b c", &mut out).unwrap();
+///
+/// assert_eq!("x=a%26b+c", out);
+/// ```
+pub fn query_escape_into<W>(s: &str, out: &mut W) -> fmt::Result
+where
+    W: fmt::Write + ?Sized,
+{
+    internal::escape_into(s.as_bytes(), Encoding::QueryComponent, out)
+}
+
+/// query_escape_bytes is like query_escape but escapes arbitrary bytes, which
+/// need not be valid UTF-8.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!("a+b%FF", net::url::query_escape_bytes(b"a b\xff"));
+/// ```
+pub fn query_escape_bytes(s: &[u8]) -> String {
+    internal::escape_bytes(s, Encoding::QueryComponent)
+}
+
 /// query_unescape does the inverse transformation of query_escape,
 /// converting each 3-byte encoded substring of the form "%AB" into the
 /// hex-decoded byte 0xAB.
 /// It returns an error if any % is not followed by two hexadecimal
-/// digits.
+/// digits, or if the decoded bytes aren't valid UTF-8.
 pub fn query_unescape(s: &str) -> Result<String, Error> {
-    internal::unescape(s, Encoding::QueryComponent)
+    internal::unescape(s, Encoding::QueryComponent).map(|v| v.into_owned())
+}
+
+/// query_unescape_bytes is like query_unescape but returns the decoded bytes,
+/// which need not be valid UTF-8.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!(b"a \xff".to_vec(), net::url::query_unescape_bytes(b"a+%FF").unwrap());
+/// ```
+pub fn query_unescape_bytes(s: &[u8]) -> Result<Vec<u8>, Error> {
+    internal::unescape_bytes(s, Encoding::QueryComponent).map(|v| v.into_owned())
+}
+
+/// query_unescape_lossy is like query_unescape but replaces invalid UTF-8
+/// sequences of the decoded bytes with U+FFFD.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!("a \u{fffd}", net::url::query_unescape_lossy("a+%FF").unwrap());
+/// ```
+pub fn query_unescape_lossy(s: &str) -> Result<String, Error> {
+    internal::unescape_lossy(s, Encoding::QueryComponent).map(|v| v.into_owned())
 }
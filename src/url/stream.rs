@@ -0,0 +1,301 @@
+use std::io;
+
+use super::internal::{self, Encoding};
+
+/// BUFFER_SIZE is the number of bytes handled at once by the adapters.
+const BUFFER_SIZE: usize = 8192;
+
+/// EscapeWriter escapes the bytes written to it, as by
+/// [query_escape_bytes](fn.query_escape_bytes.html) or
+/// [path_escape_bytes](fn.path_escape_bytes.html), before passing them on to
+/// the inner writer. Since every byte is escaped on its own, writes can be
+/// split anywhere, and a large body is escaped without buffering it whole.
+///
+/// If the inner writer fails after taking part of the escaped bytes, write
+/// reports the bytes whose escapes it started to pass on, and the error is
+/// returned by the next call. The rest of an escape cut short is kept and
+/// passed on first by the next write or flush.
+///
+/// # Example
+///
+/// ```
+/// use std::io::{self, Read, Write};
+///
+/// use net::url::{EscapeWriter, UnescapeReader};
+///
+/// let mut body = Vec::new();
+/// let mut w = EscapeWriter::query(&mut body);
+/// w.get_mut().write_all(b"note=").unwrap();
+/// io::copy(&mut &b"1 + 1 = 2"[..], &mut w).unwrap();
+///
+/// assert_eq!(b"note=1+%2B+1+%3D+2", &body[..]);
+///
+/// let mut note = String::new();
+/// UnescapeReader::query(&body[5..]).read_to_string(&mut note).unwrap();
+/// assert_eq!("1 + 1 = 2", note);
+/// ```
+#[derive(Debug)]
+pub struct EscapeWriter<W> {
+    inner: W,
+    mode: Encoding,
+    buf: String,
+    pos: usize,
+    err: Option<io::Error>,
+}
+
+impl<W: io::Write> EscapeWriter<W> {
+    /// path escapes bytes written to inner as a URL path segment.
+    pub fn path(inner: W) -> Self {
+        Self::new(inner, Encoding::PathSegment)
+    }
+
+    /// query escapes bytes written to inner as a URL query component.
+    pub fn query(inner: W) -> Self {
+        Self::new(inner, Encoding::QueryComponent)
+    }
+
+    /// get_mut returns the inner writer, to write bytes that must not be
+    /// escaped, like the separators of a query.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// get_ref returns the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// into_inner returns the inner writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn new(inner: W, mode: Encoding) -> Self {
+        Self {
+            inner,
+            mode,
+            buf: String::new(),
+            pos: 0,
+            err: None,
+        }
+    }
+
+    /// write_pending passes the escaped bytes of buf from pos on to the inner
+    /// writer, after returning any error left by the previous write.
+    fn write_pending(&mut self) -> io::Result<()> {
+        if let Some(err) = self.err.take() {
+            return Err(err);
+        }
+
+        while self.pos < self.buf.len() {
+            match self.inner.write(&self.buf.as_bytes()[self.pos..]) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => self.pos += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// started returns the number of bytes of chunk whose escapes start within
+    /// the first pos escaped bytes, and truncates buf to the end of the last
+    /// of them.
+    fn started(&mut self, chunk: &[u8]) -> usize {
+        let mut escaped = String::new();
+        let (mut n, mut end) = (0, 0);
+        for &c in chunk {
+            if end >= self.pos {
+                break;
+            }
+
+            escaped.clear();
+            let _ = internal::escape_into(&[c], self.mode, &mut escaped);
+            end += escaped.len();
+            n += 1;
+        }
+
+        self.buf.truncate(end);
+        n
+    }
+}
+
+impl<W: io::Write> io::Write for EscapeWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_pending()?;
+
+        let mut written = 0;
+        for chunk in buf.chunks(BUFFER_SIZE) {
+            self.buf.clear();
+            self.pos = 0;
+            let _ = internal::escape_into(chunk, self.mode, &mut self.buf);
+
+            if let Err(err) = self.write_pending() {
+                let n = self.started(chunk);
+                if written + n == 0 {
+                    return Err(err);
+                }
+
+                self.err = Some(err);
+                return Ok(written + n);
+            }
+
+            written += chunk.len();
+        }
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_pending()?;
+        self.inner.flush()
+    }
+}
+
+/// UnescapeReader unescapes the bytes read from the inner reader, as by
+/// [query_unescape_bytes](fn.query_unescape_bytes.html) or
+/// [path_unescape_bytes](fn.path_unescape_bytes.html), keeping escapes split
+/// across reads until they are complete. A malformed escape is reported as an
+/// error of kind InvalidData wrapping [Error](errors/enum.Error.html), after
+/// the bytes before it are read.
+///
+/// # Example
+///
+/// ```
+/// use std::io::{ErrorKind, Read};
+///
+/// use net::url::UnescapeReader;
+///
+/// let mut out = Vec::new();
+/// UnescapeReader::path(&b"a%2Fb+%FF"[..]).read_to_end(&mut out).unwrap();
+/// assert_eq!(b"a/b+\xff", &out[..]);
+///
+/// let err = UnescapeReader::path(&b"a%zz"[..]).read_to_end(&mut out).unwrap_err();
+/// assert_eq!(ErrorKind::InvalidData, err.kind());
+/// assert_eq!("invalid URL escape `%zz`", err.to_string());
+/// ```
+#[derive(Debug)]
+pub struct UnescapeReader<R> {
+    inner: R,
+    mode: Encoding,
+    buf: Vec<u8>,
+    pos: usize,
+    eof: bool,
+}
+
+impl<R: io::Read> UnescapeReader<R> {
+    /// path unescapes bytes read from inner as a URL path segment.
+    pub fn path(inner: R) -> Self {
+        Self::new(inner, Encoding::PathSegment)
+    }
+
+    /// query unescapes bytes read from inner as a URL query component,
+    /// turning '+' into ' '.
+    pub fn query(inner: R) -> Self {
+        Self::new(inner, Encoding::QueryComponent)
+    }
+
+    /// get_mut returns the inner reader. Reading from it directly skips any
+    /// bytes already buffered.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// get_ref returns the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// into_inner returns the inner reader, dropping any bytes buffered.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn new(inner: R, mode: Encoding) -> Self {
+        Self {
+            inner,
+            mode,
+            buf: Vec::with_capacity(BUFFER_SIZE),
+            pos: 0,
+            eof: false,
+        }
+    }
+
+    /// decode unescapes the buffered bytes into out, stopping before an
+    /// incomplete escape. A malformed escape is reported once the bytes
+    /// before it are returned.
+    fn decode(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let s = &self.buf;
+
+        let (mut i, mut n) = (self.pos, 0);
+        while n < out.len() && i < s.len() {
+            out[n] = match s[i] {
+                b'%' if i + 2 >= s.len() => break,
+                b'%' if !internal::is_hex(s[i + 1]) || !internal::is_hex(s[i + 2]) => {
+                    if n > 0 {
+                        break;
+                    }
+                    return Err(invalid_data(&s[i..(i + 3)]));
+                }
+                b'%' => {
+                    i += 2;
+                    (internal::unhex(s[i - 1]) << 4) | internal::unhex(s[i])
+                }
+                b'+' if self.mode == Encoding::QueryComponent => b' ',
+                c => c,
+            };
+
+            i += 1;
+            n += 1;
+        }
+        self.pos = i;
+
+        Ok(n)
+    }
+
+    /// fill reads more bytes after those left in the buffer.
+    fn fill(&mut self) -> io::Result<()> {
+        self.buf.drain(..self.pos);
+        self.pos = 0;
+
+        let len = self.buf.len();
+        self.buf.resize(BUFFER_SIZE, 0);
+        match self.inner.read(&mut self.buf[len..]) {
+            Ok(n) => {
+                self.buf.truncate(len + n);
+                self.eof = n == 0;
+                Ok(())
+            }
+            Err(err) => {
+                self.buf.truncate(len);
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<R: io::Read> io::Read for UnescapeReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if out.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            let n = self.decode(out)?;
+            if n > 0 || (self.pos == self.buf.len() && self.eof) {
+                return Ok(n);
+            }
+
+            if self.eof {
+                // an escape cut short by the end of input
+                return Err(invalid_data(&self.buf[self.pos..]));
+            }
+            self.fill()?;
+        }
+    }
+}
+
+fn invalid_data(escape: &[u8]) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, internal::escape_error(escape))
+}
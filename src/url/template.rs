@@ -0,0 +1,472 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use super::errors::{self, Error};
+use super::internal;
+use super::EncodeSet;
+
+/// RESERVED is the set escaped by the reserved expansions "{+var}" and
+/// "{#var}" and in literals, which keep the reserved characters of RFC 3986.
+const RESERVED: EncodeSet = EncodeSet::UNRESERVED.remove_all(b":/?#[]@!$&'()*+,;=");
+
+/// UriTemplate is a URI Template as defined by RFC 6570, up to level 4: a
+/// string with expressions in braces which are replaced by the values of
+/// variables.
+///
+/// Every operator is supported: simple "{var}", reserved "{+var}", fragment
+/// "{#var}", label "{.var}", path segments "{/var}", path-style parameters
+/// "{;var}", query "{?var}" and query continuation "{&var}", along with the
+/// prefix "{var:3}" and explode "{var*}" modifiers. Values are escaped with the
+/// same tables as the rest of this package, so that expansions are read back
+/// by [parse](fn.parse.html) as expected.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use net::url::{TemplateValue, UriTemplate};
+///
+/// let t: UriTemplate = "/repos/{owner}/{repo}/issues{?state,labels}".parse().unwrap();
+///
+/// let mut vars = HashMap::new();
+/// vars.insert("owner".to_string(), TemplateValue::from("rust lang"));
+/// vars.insert("repo".to_string(), "rust".into());
+/// vars.insert("labels".to_string(), vec!["A-url", "C-bug"].into());
+///
+/// let got = t.expand(&vars);
+/// assert_eq!("/repos/rust%20lang/rust/issues?labels=A-url,C-bug", got);
+///
+/// let u = net::url::parse(&got).unwrap();
+/// assert_eq!("/repos/rust lang/rust/issues", u.path);
+/// assert_eq!(Some("A-url,C-bug"), u.query().get("labels"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UriTemplate {
+    parts: Vec<Part>,
+    raw: String,
+}
+
+/// TemplateValue is the value of a variable of a
+/// [UriTemplate](struct.UriTemplate.html). Lists and maps without items are
+/// undefined, as are the variables missing from the expansion, while an empty
+/// string is defined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateValue {
+    String(String),
+    List(Vec<String>),
+    /// an associative array, whose pairs are expanded in order
+    Map(Vec<(String, String)>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Expression(Operator, Vec<VarSpec>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct VarSpec {
+    name: String,
+    modifier: Modifier,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Modifier {
+    Explode,
+    None,
+    Prefix(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Simple,
+    Reserved,
+    Fragment,
+    Label,
+    Path,
+    PathParam,
+    Query,
+    QueryContinuation,
+}
+
+impl UriTemplate {
+    /// parse parses a URI template, reporting the offset of the first
+    /// malformed literal or expression.
+    pub fn parse(template: &str) -> Result<Self, Error> {
+        let mut parts = vec![];
+
+        let mut rest = template;
+        while !rest.is_empty() {
+            let offset = template.len() - rest.len();
+
+            let end = match rest.find('{') {
+                Some(0) => {
+                    let end = rest
+                        .find('}')
+                        .ok_or_else(|| syntax_error("unclosed expression", offset, template))?;
+                    let expr = parse_expression(&rest[1..end])
+                        .ok_or_else(|| syntax_error("invalid expression", offset, template))?;
+                    parts.push(expr);
+                    rest = &rest[(end + 1)..];
+                    continue;
+                }
+                Some(i) => i,
+                None => rest.len(),
+            };
+
+            let literal = &rest[..end];
+            if let Some(i) = invalid_literal(literal) {
+                return Err(syntax_error("invalid literal", offset + i, template));
+            }
+            parts.push(Part::Literal(literal.to_string()));
+            rest = &rest[end..];
+        }
+
+        Ok(Self {
+            parts,
+            raw: template.to_string(),
+        })
+    }
+
+    /// as_str returns the template as it was parsed.
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    /// expand replaces the expressions of the template by the values of vars,
+    /// leaving out the undefined variables.
+    pub fn expand(&self, vars: &HashMap<String, TemplateValue>) -> String {
+        let mut out = String::with_capacity(self.raw.len());
+
+        for part in &self.parts {
+            match part {
+                Part::Literal(v) => escape_reserved(&mut out, v),
+                Part::Expression(op, varspecs) => op.expand(&mut out, varspecs, vars),
+            }
+        }
+
+        out
+    }
+
+    /// variables returns the names of the variables of the template, in the
+    /// order of their first use.
+    pub fn variables(&self) -> Vec<&str> {
+        let mut out: Vec<&str> = vec![];
+        for part in &self.parts {
+            if let Part::Expression(_, varspecs) = part {
+                for v in varspecs {
+                    if !out.contains(&v.name.as_str()) {
+                        out.push(&v.name);
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+impl fmt::Display for UriTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+impl FromStr for UriTemplate {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl From<&str> for TemplateValue {
+    fn from(v: &str) -> Self {
+        TemplateValue::String(v.to_string())
+    }
+}
+
+impl From<String> for TemplateValue {
+    fn from(v: String) -> Self {
+        TemplateValue::String(v)
+    }
+}
+
+impl From<Vec<&str>> for TemplateValue {
+    fn from(v: Vec<&str>) -> Self {
+        TemplateValue::List(v.into_iter().map(str::to_string).collect())
+    }
+}
+
+impl From<Vec<String>> for TemplateValue {
+    fn from(v: Vec<String>) -> Self {
+        TemplateValue::List(v)
+    }
+}
+
+impl From<Vec<(&str, &str)>> for TemplateValue {
+    fn from(v: Vec<(&str, &str)>) -> Self {
+        let pairs = v.into_iter().map(|(k, v)| (k.to_string(), v.to_string()));
+        TemplateValue::Map(pairs.collect())
+    }
+}
+
+impl From<Vec<(String, String)>> for TemplateValue {
+    fn from(v: Vec<(String, String)>) -> Self {
+        TemplateValue::Map(v)
+    }
+}
+
+impl Operator {
+    /// from_char returns the operator introduced by c, if any.
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            '+' => Some(Operator::Reserved),
+            '#' => Some(Operator::Fragment),
+            '.' => Some(Operator::Label),
+            '/' => Some(Operator::Path),
+            ';' => Some(Operator::PathParam),
+            '?' => Some(Operator::Query),
+            '&' => Some(Operator::QueryContinuation),
+            _ => None,
+        }
+    }
+
+    /// expand writes the expansion of the expression to out, following the
+    /// algorithm of RFC 6570, Appendix A.
+    fn expand(
+        &self,
+        out: &mut String,
+        varspecs: &[VarSpec],
+        vars: &HashMap<String, TemplateValue>,
+    ) {
+        let (first, sep, named, ifemp, reserved) = match self {
+            Operator::Simple => ("", ",", false, "", false),
+            Operator::Reserved => ("", ",", false, "", true),
+            Operator::Fragment => ("#", ",", false, "", true),
+            Operator::Label => (".", ".", false, "", false),
+            Operator::Path => ("/", "/", false, "", false),
+            Operator::PathParam => (";", ";", true, "", false),
+            Operator::Query => ("?", "&", true, "=", false),
+            Operator::QueryContinuation => ("&", "&", true, "=", false),
+        };
+        let escape = |out: &mut String, s: &str| {
+            if reserved {
+                escape_reserved(out, s)
+            } else {
+                let _ = internal::escape_with_into(s.as_bytes(), &EncodeSet::UNRESERVED, out);
+            }
+        };
+
+        let mut defined = 0;
+        for varspec in varspecs {
+            let value = match vars.get(&varspec.name) {
+                Some(TemplateValue::List(v)) if v.is_empty() => continue,
+                Some(TemplateValue::Map(v)) if v.is_empty() => continue,
+                Some(v) => v,
+                None => continue,
+            };
+
+            out.push_str(if defined == 0 { first } else { sep });
+            defined += 1;
+
+            let explode = varspec.modifier == Modifier::Explode;
+            match value {
+                TemplateValue::String(v) => {
+                    if named {
+                        out.push_str(&varspec.name);
+                        out.push_str(if v.is_empty() { ifemp } else { "=" });
+                    }
+                    match varspec.modifier {
+                        Modifier::Prefix(n) => escape(out, prefix(v, n)),
+                        _ => escape(out, v),
+                    }
+                }
+                TemplateValue::List(items) if explode => {
+                    for (i, v) in items.iter().enumerate() {
+                        if i > 0 {
+                            out.push_str(sep);
+                        }
+                        if named {
+                            out.push_str(&varspec.name);
+                            out.push_str(if v.is_empty() { ifemp } else { "=" });
+                        }
+                        escape(out, v);
+                    }
+                }
+                TemplateValue::Map(pairs) if explode => {
+                    for (i, (k, v)) in pairs.iter().enumerate() {
+                        if i > 0 {
+                            out.push_str(sep);
+                        }
+                        escape(out, k);
+                        if named {
+                            out.push_str(if v.is_empty() { ifemp } else { "=" });
+                        } else {
+                            out.push('=');
+                        }
+                        escape(out, v);
+                    }
+                }
+                TemplateValue::List(items) => {
+                    if named {
+                        out.push_str(&varspec.name);
+                        out.push('=');
+                    }
+                    for (i, v) in items.iter().enumerate() {
+                        if i > 0 {
+                            out.push(',');
+                        }
+                        escape(out, v);
+                    }
+                }
+                TemplateValue::Map(pairs) => {
+                    if named {
+                        out.push_str(&varspec.name);
+                        out.push('=');
+                    }
+                    for (i, (k, v)) in pairs.iter().enumerate() {
+                        if i > 0 {
+                            out.push(',');
+                        }
+                        escape(out, k);
+                        out.push(',');
+                        escape(out, v);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// escape_reserved writes s to out escaping the characters which are neither
+/// unreserved nor reserved, and keeping its %-encoded triplets.
+fn escape_reserved(out: &mut String, s: &str) {
+    let b = s.as_bytes();
+
+    let mut start = 0;
+    let mut i = 0;
+    while i < b.len() {
+        if is_escape(b, i) {
+            let _ = internal::escape_with_into(&b[start..i], &RESERVED, out);
+            out.push_str(&s[i..(i + 3)]);
+            i += 3;
+            start = i;
+        } else {
+            i += 1;
+        }
+    }
+
+    let _ = internal::escape_with_into(&b[start..], &RESERVED, out);
+}
+
+/// invalid_literal returns the offset of the first character of s which can't
+/// appear in the literals of a template, if any.
+fn invalid_literal(s: &str) -> Option<usize> {
+    let b = s.as_bytes();
+    for (i, &c) in b.iter().enumerate() {
+        match c {
+            b'%' if is_escape(b, i) => {}
+            0..=b' '
+            | 0x7f
+            | b'"'
+            | b'\''
+            | b'%'
+            | b'<'
+            | b'>'
+            | b'\\'
+            | b'^'
+            | b'`'
+            | b'{'
+            | b'|'
+            | b'}' => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// is_escape reports whether a %-encoded triplet starts at b[i].
+fn is_escape(b: &[u8], i: usize) -> bool {
+    b[i] == b'%' && i + 2 < b.len() && internal::is_hex(b[i + 1]) && internal::is_hex(b[i + 2])
+}
+
+/// parse_expression parses the content of an expression, between braces.
+fn parse_expression(s: &str) -> Option<Part> {
+    let (op, list) = match s.chars().next().and_then(Operator::from_char) {
+        Some(op) => (op, &s[1..]),
+        None => (Operator::Simple, s),
+    };
+
+    let mut varspecs = vec![];
+    for varspec in list.split(',') {
+        let (name, modifier) = if let Some(name) = varspec.strip_suffix('*') {
+            (name, Modifier::Explode)
+        } else if let Some(i) = varspec.find(':') {
+            let n = &varspec[(i + 1)..];
+            if n.is_empty()
+                || n.len() > 4
+                || n.starts_with('0')
+                || !n.bytes().all(|c| c.is_ascii_digit())
+            {
+                return None;
+            }
+            (&varspec[..i], Modifier::Prefix(n.parse().ok()?))
+        } else {
+            (varspec, Modifier::None)
+        };
+
+        if !valid_varname(name) {
+            return None;
+        }
+
+        varspecs.push(VarSpec {
+            name: name.to_string(),
+            modifier,
+        });
+    }
+
+    Some(Part::Expression(op, varspecs))
+}
+
+/// prefix returns the first n characters of s, as told by a prefix modifier.
+fn prefix(s: &str, n: usize) -> &str {
+    match s.char_indices().nth(n) {
+        Some((i, _)) => &s[..i],
+        None => s,
+    }
+}
+
+/// syntax_error reports a malformed template at the given offset.
+fn syntax_error(desc: &str, offset: usize, template: &str) -> Error {
+    let err = format!(
+        "net/url: {} in URI template {:?} at offset {}",
+        desc, template, offset
+    );
+    errors::new_misc(err)
+}
+
+/// valid_varname reports whether s is a valid varname per RFC 6570,
+/// Section 2.3:
+///     varname = varchar *( ["."] varchar )
+///     varchar = ALPHA / DIGIT / "_" / pct-encoded
+fn valid_varname(s: &str) -> bool {
+    let b = s.as_bytes();
+    if b.is_empty() || b[0] == b'.' || b[b.len() - 1] == b'.' {
+        return false;
+    }
+
+    let mut i = 0;
+    while i < b.len() {
+        match b[i] {
+            b'%' if is_escape(b, i) => i += 2,
+            b'.' if b[i - 1] == b'.' => return false,
+            c if c.is_ascii_alphanumeric() || c == b'_' || c == b'.' => {}
+            _ => return false,
+        }
+        i += 1;
+    }
+
+    true
+}
@@ -0,0 +1,240 @@
+use super::super::data::base64;
+use super::super::{DataURL, URL};
+
+#[test]
+fn base64_codec() {
+    // RFC 4648, Section 10.
+    let test_vector = vec![
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foob", "Zm9vYg=="),
+        ("fooba", "Zm9vYmE="),
+        ("foobar", "Zm9vYmFy"),
+    ];
+
+    for (decoded, encoded) in test_vector {
+        let got = base64::encode(decoded.as_bytes());
+        assert_eq!(encoded, got, "encode({:?})", decoded);
+
+        let got = base64::decode(encoded.as_bytes()).unwrap();
+        assert_eq!(decoded.as_bytes(), &got[..], "decode({:?})", encoded);
+
+        let unpadded = encoded.trim_end_matches('=');
+        let got = base64::decode(unpadded.as_bytes()).unwrap();
+        assert_eq!(decoded.as_bytes(), &got[..], "decode({:?})", unpadded);
+    }
+
+    let bytes = (0..=255).collect::<Vec<u8>>();
+    assert_eq!(
+        bytes,
+        base64::decode(base64::encode(&bytes).as_bytes()).unwrap()
+    );
+
+    assert_eq!(Ok(b"foobar".to_vec()), base64::decode(b" Zm9v\r\nYmFy\t"));
+
+    let test_vector = vec![
+        ("Z", 1),
+        ("Zm9vY", 5),
+        ("Zm9v=", 4),
+        ("Zg=", 3),
+        ("Zg===", 4),
+        ("Zg==Zg==", 4),
+        ("Zm9-", 3),
+        ("Zm\u{e9}", 2),
+    ];
+
+    for (encoded, offset) in test_vector {
+        let got = base64::decode(encoded.as_bytes());
+        assert_eq!(Err(offset), got, "decode({:?})", encoded);
+    }
+}
+
+#[test]
+fn data_url() {
+    struct Case {
+        rawurl: &'static str,
+        mime_type: &'static str,
+        base64: bool,
+        data: &'static [u8],
+    }
+
+    let new_case = |rawurl, mime_type, base64, data| Case {
+        rawurl,
+        mime_type,
+        base64,
+        data,
+    };
+
+    let test_vector = vec![
+        new_case("data:,", "text/plain;charset=US-ASCII", false, b""),
+        new_case(
+            "data:,A%20brief%20note",
+            "text/plain;charset=US-ASCII",
+            false,
+            b"A brief note",
+        ),
+        new_case(
+            "data:;charset=iso-8859-7,%be%e4%be",
+            "text/plain;charset=iso-8859-7",
+            false,
+            b"\xbe\xe4\xbe",
+        ),
+        new_case(
+            "data:Image/GIF;base64,R0lGODdh",
+            "image/gif",
+            true,
+            b"GIF87a",
+        ),
+        new_case(
+            "DATA:text/plain;Charset=%22utf-8%22;BASE64,SGVs bG8=",
+            "text/plain;charset=\"utf-8\"",
+            true,
+            b"Hello",
+        ),
+        new_case(
+            "data:text/html,<a href=\"?q=1\">a,b</a>#frag",
+            "text/html",
+            false,
+            b"<a href=\"?q=1\">a,b</a>",
+        ),
+        new_case(
+            "data:application/octet-stream;base64,/w==",
+            "application/octet-stream",
+            true,
+            b"\xff",
+        ),
+        new_case("data:text/plain;base64,", "text/plain", true, b""),
+    ];
+
+    for c in test_vector {
+        let got = match c.rawurl.parse::<DataURL>() {
+            Ok(v) => v,
+            Err(err) => panic!("parse({}) unexpected error: {}", c.rawurl, err),
+        };
+
+        assert_eq!(c.mime_type, got.mime_type(), "mime_type({})", c.rawurl);
+        assert_eq!(c.base64, got.is_base64(), "is_base64({})", c.rawurl);
+        assert_eq!(c.data, got.data(), "data({})", c.rawurl);
+
+        let u = got.to_url();
+        let again = DataURL::from_url(&super::super::parse(&u.to_string()).unwrap()).unwrap();
+        assert_eq!(got, again, "{} doesn't round trip through {}", c.rawurl, u);
+    }
+}
+
+#[test]
+fn data_url_errors() {
+    let test_vector = vec![
+        (
+            "http://example.com/",
+            r#"net/url: scheme "http" is not data"#,
+        ),
+        ("data:/a,b", "net/url: data URL without opaque content"),
+        ("data:text/plain", "net/url: missing comma in data URL"),
+        ("data:text,a", r#"net/url: invalid media type "text""#),
+        (
+            "data:text/plain;x,a",
+            r#"net/url: invalid data URL parameter "x""#,
+        ),
+        (
+            "data:text/plain;x y=1,a",
+            r#"net/url: invalid data URL parameter name "x y""#,
+        ),
+        (
+            "data:text/plain;base64;a=1,x",
+            r#"net/url: invalid data URL parameter "base64""#,
+        ),
+        ("data:,%zz", "invalid URL escape `%zz`"),
+        (
+            "data:;base64,Zm9v=",
+            "net/url: invalid base64 data in data URL at offset 4",
+        ),
+    ];
+
+    for (rawurl, expect) in test_vector {
+        let u = URL {
+            scheme: "data".to_string(),
+            ..URL::default()
+        };
+        let got = match super::super::parse(rawurl) {
+            Ok(v) => DataURL::from_url(&v),
+            Err(_) => DataURL::from_url(&u),
+        };
+
+        match got {
+            Ok(v) => panic!("DataURL::from_url({}) = {:?}, want error", rawurl, v),
+            Err(err) => assert_eq!(expect, err.to_string(), "DataURL::from_url({})", rawurl),
+        }
+    }
+}
+
+#[test]
+fn data_url_builder() {
+    struct Case {
+        builder: super::super::DataURLBuilder,
+        expect: Result<&'static str, &'static str>,
+    }
+
+    let new_case = |builder, expect| Case { builder, expect };
+
+    let test_vector = vec![
+        new_case(DataURL::builder("hi"), Ok("data:,hi")),
+        new_case(
+            DataURL::builder("a b#c?d%").mime_type("text/plain"),
+            Ok("data:text/plain,a%20b%23c%3Fd%25"),
+        ),
+        new_case(
+            DataURL::builder(vec![0u8; 12]).mime_type("application/octet-stream"),
+            Ok("data:application/octet-stream;base64,AAAAAAAAAAAAAAAA"),
+        ),
+        new_case(DataURL::builder("hi").base64(true), Ok("data:;base64,aGk=")),
+        new_case(
+            DataURL::builder("hi").param("charset", "utf-8"),
+            Ok("data:text/plain;charset=utf-8,hi"),
+        ),
+        new_case(
+            DataURL::builder("x")
+                .mime_type(r#"Text/HTML; Charset="a;b\"c"; q=1"#)
+                .param("Level", "1,2"),
+            Ok("data:text/html;charset=a%3Bb%22c;q=1;level=1%2C2,x"),
+        ),
+        new_case(
+            DataURL::builder("x").mime_type("image/"),
+            Err(r#"net/url: invalid media type "image/""#),
+        ),
+        new_case(
+            DataURL::builder("x").mime_type("text/plain; charset"),
+            Err(r#"net/url: invalid MIME type parameter "charset""#),
+        ),
+        new_case(
+            DataURL::builder("x").param("a b", "1"),
+            Err(r#"net/url: invalid MIME type parameter name "a b""#),
+        ),
+    ];
+
+    for c in test_vector {
+        let got = c.builder.build().map(|v| v.to_string());
+        let got = got.as_ref().map(String::as_str).map_err(|e| e.to_string());
+        assert_eq!(
+            c.expect.map_err(str::to_string),
+            got,
+            "{:?}.build()",
+            c.builder
+        );
+
+        if let Ok(u) = c.builder.build() {
+            let got = DataURL::from_url(&super::super::parse(&u.to_string()).unwrap()).unwrap();
+            assert_eq!(
+                &got.data(),
+                &c.builder
+                    .build()
+                    .map(|u| DataURL::from_url(&u).unwrap().into_data())
+                    .unwrap(),
+                "{} doesn't round trip",
+                u
+            );
+        }
+    }
+}
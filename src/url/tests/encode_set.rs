@@ -0,0 +1,75 @@
+use super::super::internal::{self, Encoding};
+use super::super::EncodeSet;
+
+#[test]
+fn encode_set() {
+    let test_vector = vec![
+        (EncodeSet::FRAGMENT, Encoding::Fragment),
+        (EncodeSet::HOST, Encoding::Host),
+        (EncodeSet::PATH, Encoding::Path),
+        (EncodeSet::PATH_SEGMENT, Encoding::PathSegment),
+        (EncodeSet::QUERY_COMPONENT, Encoding::QueryComponent),
+        (EncodeSet::USER_PASSWORD, Encoding::UserPassword),
+        (EncodeSet::ZONE, Encoding::Zone),
+    ];
+
+    for (set, mode) in test_vector {
+        for c in 0..=255u8 {
+            assert_eq!(
+                internal::should_escape(c, mode),
+                set.contains(c),
+                "{:?}.contains({})",
+                mode,
+                c
+            );
+        }
+        assert_eq!(mode == Encoding::QueryComponent, set.is_space_as_plus());
+    }
+
+    for c in 0..=255u8 {
+        let unreserved = c.is_ascii_alphanumeric() || b"-._~".contains(&c);
+        assert_eq!(!unreserved, EncodeSet::UNRESERVED.contains(c), "{}", c);
+        assert_eq!(!c.is_ascii(), EncodeSet::NON_ASCII.contains(c), "{}", c);
+    }
+
+    let set = EncodeSet::NON_ASCII.add(b'/').add_all(b" ?");
+    assert!(set.contains(b'/') && set.contains(b' ') && set.contains(b'?'));
+    let set = set.remove(b'/').remove_all(b" ?\xff");
+    assert_eq!(EncodeSet::NON_ASCII, set);
+}
+
+#[test]
+fn escape_with() {
+    struct Case {
+        s: &'static str,
+        set: EncodeSet,
+        expect: &'static str,
+    }
+
+    let new_case = |s, set, expect| Case { s, set, expect };
+
+    let oauth = EncodeSet::UNRESERVED;
+    let test_vector = vec![
+        // RFC 5849, §3.6
+        new_case("Ladies + Gentlemen", oauth, "Ladies%20%2B%20Gentlemen"),
+        new_case("An encoded string!", oauth, "An%20encoded%20string%21"),
+        new_case("Dogs, Cats & Mice", oauth, "Dogs%2C%20Cats%20%26%20Mice"),
+        new_case("☃", oauth, "%E2%98%83"),
+        new_case("a b+c", oauth.space_as_plus(true), "a+b%2Bc"),
+        new_case("a b", oauth.remove(b' ').space_as_plus(true), "a b"),
+        new_case("a b/c", EncodeSet::QUERY_COMPONENT, "a+b%2Fc"),
+        new_case("a b/c", EncodeSet::PATH, "a%20b/c"),
+        new_case("a/b", EncodeSet::NON_ASCII.add(b'a'), "%61/b"),
+    ];
+
+    for c in test_vector {
+        let got = super::super::escape_with(c.s, &c.set);
+        assert_eq!(c.expect, got, "escape_with({:?}, {:?})", c.s, c.set);
+
+        let back = super::super::unescape_with(&got, &c.set).unwrap();
+        assert_eq!(c.s, back, "unescape_with({:?}, {:?})", got, c.set);
+    }
+
+    let err = super::super::unescape_with("%FF", &oauth).unwrap_err();
+    assert_eq!("invalid UTF-8 in unescaped `%FF`", err.to_string());
+}
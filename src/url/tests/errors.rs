@@ -0,0 +1,93 @@
+use std::error::Error as _;
+use std::thread;
+
+use super::super::errors::{Error, ParseError, ParseErrorKind};
+
+#[test]
+fn from_escape() {
+    let test_vector = vec![
+        (
+            Error::Escape("%zz".to_string()),
+            ParseErrorKind::InvalidEscape("%zz".to_string()),
+        ),
+        (
+            Error::InvalidHost(" ".to_string()),
+            ParseErrorKind::InvalidHostCharacter(" ".to_string()),
+        ),
+        (
+            Error::InvalidUTF8("a%FF".to_string()),
+            ParseErrorKind::InvalidUTF8("a%FF".to_string()),
+        ),
+        (
+            Error::Misc("oops".to_string()),
+            ParseErrorKind::InvalidEscape("oops".to_string()),
+        ),
+    ];
+
+    for (err, expect) in test_vector {
+        let desc = format!("{:?}", err);
+        let got = ParseError::from_escape(7, err);
+        assert_eq!(expect, got.kind, "from_escape({})", desc);
+        assert_eq!(7, got.offset, "from_escape({}).offset", desc);
+    }
+}
+
+#[test]
+fn send_sync() {
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+    assert_send_sync::<Error>();
+
+    let err = thread::spawn(|| super::super::parse("http://x:8o/").unwrap_err())
+        .join()
+        .unwrap();
+    assert_eq!(
+        "parse http://x:8o/: invalid port :8o after host",
+        err.to_string()
+    );
+
+    let err: Box<dyn std::error::Error + Send + Sync> = err.into();
+    assert!(err.downcast_ref::<Error>().is_some());
+}
+
+#[test]
+fn source() {
+    struct Case {
+        rawurl: &'static str,
+        expect: Vec<&'static str>,
+    }
+
+    let new_case = |rawurl, expect| Case { rawurl, expect };
+
+    let test_vector = vec![
+        new_case(
+            ":foo",
+            vec![
+                "parse :foo: missing protocol scheme",
+                "missing protocol scheme",
+            ],
+        ),
+        new_case(
+            "http://x/#%zz",
+            vec![
+                "parse http://x/#%zz: invalid URL escape `%zz`",
+                "invalid URL escape `%zz`",
+            ],
+        ),
+    ];
+
+    for c in test_vector {
+        let err = super::super::parse(c.rawurl).unwrap_err();
+
+        let mut chain = vec![];
+        let mut next: Option<&(dyn std::error::Error + 'static)> = Some(&err);
+        while let Some(err) = next {
+            chain.push(err.to_string());
+            next = err.source();
+        }
+
+        assert_eq!(c.expect, chain, "source chain of parse({:?})", c.rawurl);
+    }
+
+    let err = super::super::query_unescape("%zz").unwrap_err();
+    assert!(err.source().is_none(), "{:?}", err);
+}
@@ -0,0 +1,135 @@
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+
+use super::super::URL;
+
+#[test]
+fn from_file_path() {
+    struct Case {
+        path: &'static [u8],
+        url: &'static str,
+        dir: &'static str,
+        back: &'static [u8],
+    }
+
+    let new_case = |path, url, dir, back| Case {
+        path,
+        url,
+        dir,
+        back,
+    };
+
+    let test_vector = vec![
+        new_case(b"/", "file:///", "file:///", b"/"),
+        new_case(
+            b"/a/b.txt",
+            "file:///a/b.txt",
+            "file:///a/b.txt/",
+            b"/a/b.txt",
+        ),
+        new_case(b"/a//b/./c/", "file:///a/b/c", "file:///a/b/c/", b"/a/b/c"),
+        new_case(b"/a/../b", "file:///a/../b", "file:///a/../b/", b"/a/../b"),
+        new_case(
+            b"/a b/%41/#?;,=",
+            "file:///a%20b/%2541/%23%3F;,=",
+            "file:///a%20b/%2541/%23%3F;,=/",
+            b"/a b/%41/#?;,=",
+        ),
+        new_case(
+            "/tmp/café".as_bytes(),
+            "file:///tmp/caf%C3%A9",
+            "file:///tmp/caf%C3%A9/",
+            "/tmp/café".as_bytes(),
+        ),
+        new_case(
+            b"/tmp/caf\xe9/\xff",
+            "file:///tmp/caf%E9/%FF",
+            "file:///tmp/caf%E9/%FF/",
+            b"/tmp/caf\xe9/\xff",
+        ),
+        new_case(
+            b"/a\\b:c",
+            "file:///a%5Cb:c",
+            "file:///a%5Cb:c/",
+            b"/a\\b:c",
+        ),
+    ];
+
+    for c in test_vector {
+        let path = OsStr::from_bytes(c.path);
+
+        let u = URL::from_file_path(path).unwrap();
+        assert_eq!(c.url, u.to_string(), "from_file_path({:?})", path);
+        assert_eq!(
+            u,
+            super::super::parse(c.url).unwrap(),
+            "from_file_path({:?}) doesn't parse back",
+            path
+        );
+
+        let got = u.to_file_path().unwrap();
+        assert_eq!(
+            OsStr::from_bytes(c.back),
+            got.as_os_str(),
+            "{}.to_file_path()",
+            u
+        );
+
+        let u = URL::from_directory_path(path).unwrap();
+        assert_eq!(c.dir, u.to_string(), "from_directory_path({:?})", path);
+    }
+
+    for path in &["", "a/b", "./a", "../a"] {
+        assert!(
+            URL::from_file_path(path).is_err(),
+            "from_file_path({:?}) should fail",
+            path
+        );
+        assert!(
+            URL::from_directory_path(path).is_err(),
+            "from_directory_path({:?}) should fail",
+            path
+        );
+    }
+}
+
+#[test]
+fn to_file_path() {
+    let test_vector = vec![
+        ("file:///etc/fstab", Ok("/etc/fstab")),
+        ("file://localhost/etc/fstab", Ok("/etc/fstab")),
+        ("FILE://LocalHost/etc/fstab", Ok("/etc/fstab")),
+        ("file:/etc/fstab", Ok("/etc/fstab")),
+        ("file:///a%2Fb%20c?q=1#f", Ok("/a/b c")),
+        ("file://", Err("net/url: file URL without absolute path")),
+        (
+            "file:etc/fstab",
+            Err("net/url: file URL without absolute path"),
+        ),
+        (
+            "file://example.com/etc/fstab",
+            Err(r#"net/url: file URL with non-local host "example.com""#),
+        ),
+        (
+            "file://localhost:8080/etc/fstab",
+            Err(r#"net/url: file URL with non-local host "localhost:8080""#),
+        ),
+        ("file:///a%00b", Err("net/url: NUL byte in file URL path")),
+        (
+            "http://localhost/etc/fstab",
+            Err(r#"net/url: scheme "http" is not file"#),
+        ),
+    ];
+
+    for (rawurl, expect) in test_vector {
+        let u = super::super::parse(rawurl).unwrap();
+        let got = u.to_file_path().map_err(|e| e.to_string());
+        assert_eq!(
+            expect.map(PathBuf::from).map_err(str::to_string),
+            got,
+            "{}.to_file_path()",
+            rawurl
+        );
+    }
+}
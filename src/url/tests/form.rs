@@ -0,0 +1,170 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use super::super::errors::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Kind {
+    Plain,
+    SignedUp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+struct Id(u64);
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Form {
+    name: String,
+    age: u8,
+    score: f64,
+    admin: bool,
+    initial: char,
+    id: Id,
+    kind: Kind,
+    tags: Vec<String>,
+    pair: (i32, i32),
+    nickname: Option<String>,
+    limit: Option<u32>,
+}
+
+#[test]
+fn from_query_str() {
+    let got: Form = super::super::from_query_str(
+        "name=J%C3%BCrgen+M&age=42&score=-1.5&admin=true&initial=J&id=7&kind=signed_up\
+         &tags=a&tags=b%26c&pair=1&pair=-2&limit=10&unknown=x",
+    )
+    .unwrap();
+
+    let expect = Form {
+        name: "Jürgen M".to_string(),
+        age: 42,
+        score: -1.5,
+        admin: true,
+        initial: 'J',
+        id: Id(7),
+        kind: Kind::SignedUp,
+        tags: vec!["a".to_string(), "b&c".to_string()],
+        pair: (1, -2),
+        nickname: None,
+        limit: Some(10),
+    };
+    assert_eq!(expect, got);
+
+    let got: HashMap<String, Vec<u32>> = super::super::from_query_str("a=1&b=2&a=3").unwrap();
+    assert_eq!(Some(&vec![1, 3]), got.get("a"));
+    assert_eq!(Some(&vec![2]), got.get("b"));
+
+    let got: BTreeMap<String, String> = super::super::from_query_str("").unwrap();
+    assert!(got.is_empty());
+}
+
+#[test]
+fn from_query_str_errors() {
+    #[derive(Debug, Deserialize)]
+    struct Small {
+        #[allow(dead_code)]
+        n: u8,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct OneKind {
+        #[allow(dead_code)]
+        kind: Kind,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Nested {
+        #[allow(dead_code)]
+        inner: Small,
+    }
+
+    let err = |v: Result<Small, Error>| v.unwrap_err().to_string();
+
+    assert_eq!(
+        r#"net/url: invalid value "x" for key "n": invalid digit found in string"#,
+        err(super::super::from_query_str("n=x"))
+    );
+    assert_eq!(
+        r#"net/url: invalid value "256" for key "n": number too large to fit in target type"#,
+        err(super::super::from_query_str("n=256"))
+    );
+    assert_eq!(
+        r#"net/url: expected a single value for key "n", got 2"#,
+        err(super::super::from_query_str("n=1&n=2"))
+    );
+    assert_eq!(
+        "net/url: missing field `n`",
+        err(super::super::from_query_str("m=1"))
+    );
+    assert_eq!(
+        "invalid URL escape `%zz`",
+        err(super::super::from_query_str("n=%zz"))
+    );
+
+    let got = super::super::from_query_str::<OneKind>("kind=other").unwrap_err();
+    assert_eq!(
+        "net/url: unknown variant `other`, expected `plain` or `signed_up`",
+        got.to_string()
+    );
+
+    let got = super::super::from_query_str::<Nested>("inner=1").unwrap_err();
+    assert_eq!(
+        r#"net/url: unsupported nested struct for key "inner""#,
+        got.to_string()
+    );
+}
+
+#[test]
+fn to_query_string() {
+    let form = Form {
+        name: "Jürgen M".to_string(),
+        age: 42,
+        score: -1.5,
+        admin: false,
+        initial: '&',
+        id: Id(7),
+        kind: Kind::Plain,
+        tags: vec!["a".to_string(), "b=c".to_string()],
+        pair: (1, -2),
+        nickname: None,
+        limit: Some(10),
+    };
+
+    let got = super::super::to_query_string(&form).unwrap();
+    assert_eq!(
+        "name=J%C3%BCrgen+M&age=42&score=-1.5&admin=false&initial=%26&id=7&kind=plain\
+         &tags=a&tags=b%3Dc&pair=1&pair=-2&limit=10",
+        got
+    );
+
+    let back: Form = super::super::from_query_str(&got).unwrap();
+    assert_eq!(form, back);
+
+    let mut m = BTreeMap::new();
+    m.insert(2, vec!["x", "y"]);
+    m.insert(1, vec![]);
+    assert_eq!("2=x&2=y", super::super::to_query_string(&m).unwrap());
+
+    #[derive(Serialize)]
+    struct Nested {
+        inner: Id,
+        map: BTreeMap<String, String>,
+    }
+
+    let got = super::super::to_query_string(&Nested {
+        inner: Id(1),
+        map: BTreeMap::new(),
+    });
+    assert_eq!(
+        r#"net/url: unsupported nested map for key "map""#,
+        got.unwrap_err().to_string()
+    );
+
+    let got = super::super::to_query_string(&vec![1, 2]);
+    assert_eq!(
+        "net/url: top-level value must be a struct or a map, got sequence",
+        got.unwrap_err().to_string()
+    );
+}
@@ -0,0 +1,196 @@
+use super::super::idna;
+
+#[test]
+fn punycode() {
+    struct Case {
+        decoded: &'static str,
+        encoded: &'static str,
+    }
+
+    let new_case = |decoded, encoded| -> Case { Case { decoded, encoded } };
+
+    // RFC 3492 §7.1 and https://github.com/mathiasbynens/punycode.js
+    let test_vector = vec![
+        new_case("", ""),
+        new_case("Bach", "Bach-"),
+        new_case("ü", "tda"),
+        new_case("üëäö♥", "4can8av2009b"),
+        new_case("bücher", "bcher-kva"),
+        new_case("ليهمابتكلموشعربي؟", "egbpdaj6bu4bxfgehfvwxn"),
+        new_case("他们为什么不说中文", "ihqwcrb4cv8a8dqg056pqjye"),
+        new_case("Pročprostěnemluvíčesky", "Proprostnemluvesky-uyb24dma41a"),
+        new_case(
+            "なぜみんな日本語を話してくれないのか",
+            "n8jok5ay5dzabd5bym9f0cm5685rrjetr6pdxa",
+        ),
+        new_case(
+            "почемужеонинеговорятпорусски",
+            "b1abfaaepdrnnbgefbadotcwatmq2g4l",
+        ),
+        new_case(
+            "TạisaohọkhôngthểchỉnóitiếngViệt",
+            "TisaohkhngthchnitingVit-kjcr8268qyxafd2f1b9g",
+        ),
+        new_case("3年B組金八先生", "3B-ww4c5e180e575a65lsy2b"),
+        new_case(
+            "安室奈美恵-with-SUPER-MONKEYS",
+            "-with-SUPER-MONKEYS-pc58ag80a8qai00g7n9n",
+        ),
+        new_case("ひとつ屋根の下2", "2-u9tlzr9756bt3uc0v"),
+        new_case("MajiでKoiする5秒前", "MajiKoi5-783gue6qz075azm5e"),
+        new_case("-> $1.00 <-", "-> $1.00 <--"),
+    ];
+
+    for c in test_vector {
+        let got = idna::punycode_encode(c.decoded);
+        assert_eq!(Some(c.encoded), got.as_deref(), "encode({})", c.decoded);
+
+        let got = idna::punycode_decode(c.encoded);
+        assert_eq!(Some(c.decoded), got.as_deref(), "decode({})", c.encoded);
+    }
+
+    for s in &["ü", "99999999999a", "b-é", "a-z9999999999"] {
+        assert!(
+            idna::punycode_decode(s).is_none(),
+            "decode({}) should fail",
+            s
+        );
+    }
+}
+
+#[test]
+fn to_ascii_and_unicode() {
+    struct Case {
+        unicode: &'static str,
+        ascii: Option<&'static str>,
+    }
+
+    let new_case = |unicode, ascii| -> Case { Case { unicode, ascii } };
+
+    let test_vector = vec![
+        new_case("example.com", Some("example.com")),
+        new_case("Example.COM.", Some("example.com.")),
+        new_case("bücher.example", Some("xn--bcher-kva.example")),
+        new_case("BÜCHER.example", Some("xn--bcher-kva.example")),
+        new_case("xn--bcher-kva.example", Some("xn--bcher-kva.example")),
+        new_case("日本語。ＪＰ", Some("xn--wgv71a119e.jp")),
+        new_case("fa\u{ad}ß.de", Some("xn--fa-hia.de")),
+        new_case("münchen.de", Some("xn--mnchen-3ya.de")),
+        // decomposed characters are normalized to NFC
+        new_case("bu\u{308}cher.example", Some("xn--bcher-kva.example")),
+        new_case("BU\u{308}CHER.example", Some("xn--bcher-kva.example")),
+        new_case("a\u{30a}.example", Some("xn--5ca.example")),
+        new_case("-bücher.example", None),
+        new_case("ab--c.example", None),
+        new_case("xn--.example", None),
+        new_case("xn--bcher-kva-.example", None),
+        new_case("xn--Bach-.example", None),
+        new_case("a..example", None),
+        // STD3 rules
+        new_case("a b.example", None),
+        new_case("a_b.example", None),
+        new_case("a*.example", None),
+        new_case("a-b1.example", Some("a-b1.example")),
+        new_case("a\u{3000}b.example", None),
+        new_case("a\nb.example", None),
+    ];
+
+    for c in test_vector {
+        let got = idna::domain_to_ascii(c.unicode);
+        match c.ascii {
+            Some(v) => assert_eq!(v, got.unwrap(), "to_ascii({})", c.unicode),
+            None => assert!(got.is_err(), "to_ascii({}) wants an error", c.unicode),
+        }
+    }
+
+    let long = "a".repeat(64) + ".example";
+    assert!(idna::domain_to_ascii(&long).is_err());
+    assert!(idna::to_ascii(&long, false).is_ok());
+    assert!(idna::to_ascii("a b.example", false).is_ok());
+
+    let test_vector = vec![
+        ("xn--bcher-kva.example", "bücher.example"),
+        ("XN--BCHER-KVA.Example", "bücher.example"),
+        ("xn--wgv71a119e.jp", "日本語.jp"),
+        ("bücher.example", "bücher.example"),
+        ("bu\u{308}cher.example", "bücher.example"),
+        ("\u{1100}\u{1161}\u{11a8}.example", "\u{ac01}.example"),
+        ("\u{ac00}\u{11a8}.example", "\u{ac01}.example"),
+        ("a\u{323}\u{302}.example", "\u{1ead}.example"),
+        ("a\u{302}\u{323}.example", "\u{1ead}.example"),
+        ("e\u{301}\u{301}.example", "\u{e9}\u{301}.example"),
+        ("\u{f900}.example", "\u{8c48}.example"),
+    ];
+
+    for (ascii, unicode) in test_vector {
+        let got = idna::domain_to_unicode(ascii).unwrap();
+        assert_eq!(unicode, got, "to_unicode({})", ascii);
+    }
+
+    assert!(idna::domain_to_unicode("xn--a.example").is_err());
+}
+
+#[test]
+fn parse_idna() {
+    struct Case {
+        rawurl: &'static str,
+        out: Option<&'static str>,
+    }
+
+    let new_case = |rawurl, out| -> Case { Case { rawurl, out } };
+
+    let test_vector = vec![
+        new_case("http://example.com/", Some("http://example.com/")),
+        new_case("http://my_host:80/", Some("http://my_host:80/")),
+        new_case(
+            "http://bücher.example/",
+            Some("http://xn--bcher-kva.example/"),
+        ),
+        new_case(
+            "http://user@Bu%CC%88cher.example:8080/a?b#c",
+            Some("http://user@xn--bcher-kva.example:8080/a?b#c"),
+        ),
+        new_case(
+            "http://%E6%97%A5%E6%9C%AC%E8%AA%9E%E3%80%82jp/",
+            Some("http://xn--wgv71a119e.jp/"),
+        ),
+        new_case("http://[fe80::1%25en0]/", Some("http://[fe80::1%25en0]/")),
+        new_case("/relative", Some("/relative")),
+        new_case("http://bücher .example/", None),
+        new_case("http://-bücher.example/", None),
+    ];
+
+    for c in test_vector {
+        let got = super::super::parse_idna(c.rawurl);
+        match c.out {
+            Some(v) => assert_eq!(v, got.unwrap().to_string(), "parse_idna({})", c.rawurl),
+            None => assert!(got.is_err(), "parse_idna({}) wants an error", c.rawurl),
+        }
+    }
+
+    let u = super::super::parse("http://bücher.example/").unwrap();
+    assert_eq!("http://b%C3%BCcher.example/", u.to_string());
+}
+
+#[test]
+fn host_ascii() {
+    let test_vector = vec![
+        ("http://example.com/", Some("example.com")),
+        ("http://my_host.example:80/", Some("my_host.example:80")),
+        (
+            "http://bücher.example:8080/",
+            Some("xn--bcher-kva.example:8080"),
+        ),
+        ("http://[fe80::1%25en0]:80/", Some("[fe80::1%en0]:80")),
+        ("/relative", Some("")),
+        ("http://b%C3%BCcher_x.example/", None),
+    ];
+
+    for (rawurl, want) in test_vector {
+        let got = super::super::parse(rawurl).unwrap().host_ascii();
+        match want {
+            Some(v) => assert_eq!(v, got.unwrap(), "host_ascii({})", rawurl),
+            None => assert!(got.is_err(), "host_ascii({}) wants an error", rawurl),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use super::super::internal::Encoding;
+use super::super::EncodeSet;
 
 #[test]
 fn should_escape() {
@@ -75,3 +76,77 @@ fn should_escape() {
         assert_eq!(c.escape, got, "should_escape({}, {:?}) failed", c.c, c.mode);
     }
 }
+
+#[test]
+fn find_escape() {
+    let modes = [
+        Encoding::Path,
+        Encoding::PathSegment,
+        Encoding::Host,
+        Encoding::Zone,
+        Encoding::UserPassword,
+        Encoding::QueryComponent,
+        Encoding::Fragment,
+    ];
+
+    // sets escaping some unreserved characters are looked up byte by byte
+    let mut sets = modes.iter().map(|v| *v.set()).collect::<Vec<_>>();
+    sets.push(EncodeSet::UNRESERVED.add(b'q'));
+    sets.push(EncodeSet::PATH_SEGMENT.add(b'~'));
+
+    // every byte at every position of inputs spanning several words
+    let clean = b"abcdefghijklmnopqrstuvwxyz0123456789-._~ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    for set in &sets {
+        for len in 0..clean.len() {
+            let expect = clean[..len].iter().position(|&c| set.contains(c));
+            assert_eq!(
+                expect,
+                super::super::internal::find_escape(&clean[..len], set),
+                "find_escape({:?}, {:?})",
+                &clean[..len],
+                set
+            );
+
+            for c in 0..=255u8 {
+                let mut s = clean[..=len].to_vec();
+                s[len] = c;
+
+                let expect = s.iter().position(|&c| set.contains(c));
+                let got = super::super::internal::find_escape(&s, set);
+                assert_eq!(expect, got, "find_escape({:?}, {:?})", s, set);
+            }
+        }
+    }
+}
+
+#[test]
+fn count_escapes() {
+    let sets = [
+        EncodeSet::PATH_SEGMENT,
+        EncodeSet::QUERY_COMPONENT,
+        EncodeSet::HOST,
+        EncodeSet::UNRESERVED.add(b'q'),
+    ];
+
+    let inputs = [
+        "",
+        "abc",
+        "a b",
+        "hello world, this is a longer input/with?many=escapes&more",
+        "caf\u{e9} cr\u{e8}me br\u{fb}l\u{e9}e",
+        "~~~~~~~~........--------________qqqqqqqq",
+        "0123456789abcdef0123456789abcdef\u{1f600}",
+    ];
+
+    for set in &sets {
+        for s in &inputs {
+            let b = s.as_bytes();
+            let expect = b.iter().position(|&c| set.contains(c)).map(|i| {
+                let n = b.iter().filter(|&&c| set.contains(c)).count();
+                (i, n)
+            });
+            let got = super::super::internal::count_escapes(b, set);
+            assert_eq!(expect, got, "count_escapes({:?}, {:?})", s, set);
+        }
+    }
+}
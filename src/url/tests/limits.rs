@@ -0,0 +1,204 @@
+use super::super::errors::{Error, LimitExceeded};
+use super::super::{Limits, ParseQueryOptions};
+
+#[test]
+fn parse_with_limits() {
+    struct Case {
+        rawurl: String,
+        limits: Limits,
+        err: Option<LimitExceeded>,
+    }
+
+    let new_case = |rawurl: &str, limits, err| Case {
+        rawurl: rawurl.to_string(),
+        limits,
+        err,
+    };
+
+    let url_len = |v| Limits {
+        max_url_len: v,
+        ..Default::default()
+    };
+    let host_len = |v| Limits {
+        max_host_len: v,
+        ..Default::default()
+    };
+    let labels = |v| Limits {
+        max_labels: v,
+        ..Default::default()
+    };
+
+    let test_vector = vec![
+        new_case("http://example.com/a?q=1#f", Limits::default(), None),
+        new_case("http://example.com/a?q=1#f", Limits::NONE, None),
+        new_case("http://example.com/", url_len(19), None),
+        new_case(
+            "http://example.com/",
+            url_len(18),
+            Some(LimitExceeded::URLLength(18)),
+        ),
+        new_case("http://example.com:8080/", host_len(11), None),
+        new_case(
+            "http://example.com:8080/",
+            host_len(10),
+            Some(LimitExceeded::HostLength(10)),
+        ),
+        new_case("http://[fe80::1%25en0]/", host_len(11), None),
+        new_case("http://a.b.example.com/", labels(4), None),
+        new_case(
+            "http://a.b.example.com/",
+            labels(3),
+            Some(LimitExceeded::Labels(3)),
+        ),
+        new_case("mailto:a.b.c@example.com", labels(1), None),
+        new_case("/a.b.c", labels(1), None),
+        new_case(
+            &format!("http://{}/", "a.".repeat(200)),
+            Limits::default(),
+            Some(LimitExceeded::HostLength(253)),
+        ),
+        new_case(
+            &format!("http://{}/", "a.".repeat(127)),
+            host_len(usize::MAX),
+            Some(LimitExceeded::Labels(127)),
+        ),
+    ];
+
+    for c in test_vector {
+        let got = super::super::parse_with_limits(&c.rawurl, &c.limits);
+        match (&c.err, got) {
+            (None, Ok(u)) => assert_eq!(c.rawurl, u.to_string()),
+            (None, Err(err)) => panic!("parse_with_limits({}): unexpected {}", c.rawurl, err),
+            (Some(expect), Ok(_)) => {
+                panic!("parse_with_limits({}): want error {}", c.rawurl, expect)
+            }
+            (Some(expect), Err(Error::Limit(err))) => assert_eq!(
+                expect, &err,
+                "parse_with_limits({}): got {}, want {}",
+                c.rawurl, err, expect
+            ),
+            (Some(expect), Err(Error::Wrapped { op, url, err })) => {
+                assert_eq!("parse", op);
+                assert_eq!(c.rawurl, url);
+                assert_eq!(
+                    expect.to_string(),
+                    err.to_string(),
+                    "parse_with_limits({}): got {}, want {}",
+                    c.rawurl,
+                    err,
+                    expect
+                );
+            }
+            (Some(_), Err(err)) => panic!("parse_with_limits({}): unexpected {:?}", c.rawurl, err),
+        }
+    }
+}
+
+#[test]
+fn parse_query_limits() {
+    struct Case {
+        s: String,
+        limits: Limits,
+        pairs: usize,
+        err: Option<LimitExceeded>,
+    }
+
+    let new_case = |s: &str, limits, pairs, err| Case {
+        s: s.to_string(),
+        limits,
+        pairs,
+        err,
+    };
+
+    let query_pairs = |v| Limits {
+        max_query_pairs: v,
+        ..Default::default()
+    };
+    let values_per_key = |v| Limits {
+        max_values_per_key: v,
+        ..Default::default()
+    };
+    let decoded_len = |v| Limits {
+        max_decoded_len: v,
+        ..Default::default()
+    };
+
+    let test_vector = vec![
+        new_case("a=1&b=2&c=3", query_pairs(3), 3, None),
+        new_case(
+            "a=1&b=2&c=3&d=4",
+            query_pairs(3),
+            3,
+            Some(LimitExceeded::QueryPairs(3)),
+        ),
+        // Malformed pairs count as well.
+        new_case(
+            "a=%zz&b=%zz&c=1",
+            query_pairs(2),
+            0,
+            Some(LimitExceeded::QueryPairs(2)),
+        ),
+        new_case("a=1&b=1&a=2&b=2", values_per_key(2), 4, None),
+        new_case(
+            "a=1&b=1&a=2&a=3",
+            values_per_key(2),
+            3,
+            Some(LimitExceeded::ValuesPerKey {
+                key: "a".to_string(),
+                limit: 2,
+            }),
+        ),
+        new_case("ab=c%20d", decoded_len(5), 1, None),
+        new_case(
+            "a=1&ab=c%20d",
+            decoded_len(5),
+            1,
+            Some(LimitExceeded::DecodedLength(5)),
+        ),
+        new_case(
+            &"a&".repeat(1001),
+            Limits::default(),
+            100,
+            Some(LimitExceeded::ValuesPerKey {
+                key: "a".to_string(),
+                limit: 100,
+            }),
+        ),
+        new_case(&"a&".repeat(1001), Limits::NONE, 1001, None),
+    ];
+
+    for c in test_vector {
+        let options = ParseQueryOptions {
+            limits: c.limits,
+            ..Default::default()
+        };
+
+        let (got, err) = match super::super::parse_query_ordered_with(&c.s, &options) {
+            Ok(v) => (v.0.len(), None),
+            Err((v, Error::Limit(err))) => (v.0.len(), Some(err)),
+            Err((v, _)) => (v.0.len(), None),
+        };
+        assert_eq!(
+            c.pairs, got,
+            "parse_query_ordered_with({}) got {} pairs, want {}",
+            c.s, got, c.pairs
+        );
+        assert_eq!(c.err, err, "parse_query_ordered_with({})", c.s);
+
+        let (got, err) = match super::super::parse_query_with(&c.s, &options) {
+            Ok(v) => (v.0.values().map(Vec::len).sum::<usize>(), None),
+            Err((v, Error::Limit(err))) => (v.0.values().map(Vec::len).sum(), Some(err)),
+            Err((v, _)) => (v.0.values().map(Vec::len).sum(), None),
+        };
+        assert_eq!(
+            c.pairs, got,
+            "parse_query_with({}) got {} pairs, want {}",
+            c.s, got, c.pairs
+        );
+        assert_eq!(c.err, err, "parse_query_with({})", c.s);
+    }
+
+    // parse_query sets no limits.
+    let v = super::super::parse_query(&"a&".repeat(1001)).unwrap();
+    assert_eq!(1001, v.0["a"].len());
+}
@@ -0,0 +1,197 @@
+use super::super::MailtoURL;
+
+#[test]
+fn mailto_url() {
+    struct Case {
+        rawurl: &'static str,
+        expect: MailtoURL,
+        canonical: &'static str,
+    }
+
+    let new_case = |rawurl, expect, canonical| Case {
+        rawurl,
+        expect,
+        canonical,
+    };
+
+    let strings = |v: &[&str]| v.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+    let some = |v: &str| Some(v.to_string());
+
+    // Examples of RFC 6068, Section 6.
+    let test_vector = vec![
+        new_case(
+            "mailto:chris@example.com",
+            MailtoURL {
+                to: strings(&["chris@example.com"]),
+                ..Default::default()
+            },
+            "mailto:chris@example.com",
+        ),
+        new_case(
+            "mailto:infobot@example.com?subject=current-issue",
+            MailtoURL {
+                to: strings(&["infobot@example.com"]),
+                subject: some("current-issue"),
+                ..Default::default()
+            },
+            "mailto:infobot@example.com?subject=current-issue",
+        ),
+        new_case(
+            "mailto:infobot@example.com?body=send%20current-issue%0D%0Asend%20index",
+            MailtoURL {
+                to: strings(&["infobot@example.com"]),
+                body: some("send current-issue\r\nsend index"),
+                ..Default::default()
+            },
+            "mailto:infobot@example.com?body=send%20current-issue%0D%0Asend%20index",
+        ),
+        new_case(
+            "mailto:list@example.org?In-Reply-To=%3C3469A91.D10AF4C@example.com%3E",
+            MailtoURL {
+                to: strings(&["list@example.org"]),
+                headers: vec![(
+                    "In-Reply-To".to_string(),
+                    "<3469A91.D10AF4C@example.com>".to_string(),
+                )],
+                ..Default::default()
+            },
+            "mailto:list@example.org?In-Reply-To=%3C3469A91.D10AF4C@example.com%3E",
+        ),
+        new_case(
+            "mailto:joe@example.com?cc=bob@example.com&body=hello",
+            MailtoURL {
+                to: strings(&["joe@example.com"]),
+                cc: strings(&["bob@example.com"]),
+                body: some("hello"),
+                ..Default::default()
+            },
+            "mailto:joe@example.com?cc=bob@example.com&body=hello",
+        ),
+        new_case(
+            "mailto:?to=joe@example.com&cc=bob@example.com&body=hello",
+            MailtoURL {
+                to: strings(&["joe@example.com"]),
+                cc: strings(&["bob@example.com"]),
+                body: some("hello"),
+                ..Default::default()
+            },
+            "mailto:joe@example.com?cc=bob@example.com&body=hello",
+        ),
+        new_case(
+            "mailto:gorby%25kremvax@example.com",
+            MailtoURL {
+                to: strings(&["gorby%kremvax@example.com"]),
+                ..Default::default()
+            },
+            "mailto:gorby%25kremvax@example.com",
+        ),
+        new_case(
+            "mailto:Mike%26family@example.org",
+            MailtoURL {
+                to: strings(&["Mike&family@example.org"]),
+                ..Default::default()
+            },
+            "mailto:Mike%26family@example.org",
+        ),
+        new_case(
+            "mailto:%22not%40me%22@example.org",
+            MailtoURL {
+                to: strings(&["\"not@me\"@example.org"]),
+                ..Default::default()
+            },
+            "mailto:%22not@me%22@example.org",
+        ),
+        new_case(
+            "mailto:user@example.org?subject=caf%C3%A9",
+            MailtoURL {
+                to: strings(&["user@example.org"]),
+                subject: some("café"),
+                ..Default::default()
+            },
+            "mailto:user@example.org?subject=caf%C3%A9",
+        ),
+        // '+' stands for itself, and the fragment is ignored.
+        new_case(
+            "mailto:a+tag@example.com,b@example.com?TO=c@example.com&BCC=d@example.com,e@example.com&Subject=1+1#frag",
+            MailtoURL {
+                to: strings(&["a+tag@example.com", "b@example.com", "c@example.com"]),
+                bcc: strings(&["d@example.com", "e@example.com"]),
+                subject: some("1+1"),
+                ..Default::default()
+            },
+            "mailto:a+tag@example.com,b@example.com,c@example.com?bcc=d@example.com,e@example.com&subject=1+1",
+        ),
+        new_case(
+            "mailto:a%2Cb@example.com?body=x%0Ay%0Dz",
+            MailtoURL {
+                to: strings(&["a,b@example.com"]),
+                body: some("x\ny\rz"),
+                ..Default::default()
+            },
+            "mailto:a%2Cb@example.com?body=x%0D%0Ay%0D%0Az",
+        ),
+        new_case("mailto:", MailtoURL::default(), "mailto:"),
+    ];
+
+    for c in test_vector {
+        let got = match c.rawurl.parse::<MailtoURL>() {
+            Ok(v) => v,
+            Err(err) => panic!("parse({}) unexpected error: {}", c.rawurl, err),
+        };
+        assert_eq!(c.expect, got, "parse({})", c.rawurl);
+
+        let s = got.to_string();
+        assert_eq!(c.canonical, s, "parse({}).to_string()", c.rawurl);
+        assert_eq!(
+            s,
+            got.to_url().to_string(),
+            "to_url() differs from to_string()"
+        );
+
+        let mut expect = got.clone();
+        if let Some(body) = &mut expect.body {
+            *body = body
+                .replace("\r\n", "\n")
+                .replace('\r', "\n")
+                .replace('\n', "\r\n");
+        }
+        let again = s.parse::<MailtoURL>().unwrap();
+        assert_eq!(expect, again, "{} doesn't round trip", s);
+    }
+
+    let m: MailtoURL = "mailto:?Reply-To=x@example.com".parse().unwrap();
+    assert_eq!(Some("x@example.com"), m.header("reply-to"));
+    assert_eq!(None, m.header("subject"));
+}
+
+#[test]
+fn mailto_url_errors() {
+    let test_vector = vec![
+        (
+            "http://example.com/",
+            r#"net/url: scheme "http" is not mailto"#,
+        ),
+        (
+            "mailto://example.com",
+            "net/url: mailto URL with authority or absolute path",
+        ),
+        (
+            "mailto:/a@example.com",
+            "net/url: mailto URL with authority or absolute path",
+        ),
+        (
+            "mailto:a@example.com?subject",
+            r#"net/url: invalid mailto header field "subject""#,
+        ),
+        ("mailto:a%zz@example.com", "invalid URL escape `%zz`"),
+        ("mailto:a@example.com?body=%", "invalid URL escape `%`"),
+    ];
+
+    for (rawurl, expect) in test_vector {
+        let u = super::super::parse(rawurl).unwrap();
+        match MailtoURL::from_url(&u) {
+            Ok(v) => panic!("MailtoURL::from_url({}) = {:?}, want error", rawurl, v),
+            Err(err) => assert_eq!(expect, err.to_string(), "MailtoURL::from_url({})", rawurl),
+        }
+    }
+}
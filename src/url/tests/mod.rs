@@ -56,6 +56,67 @@ fn unescape() {
     }
 }
 
+#[test]
+fn unescape_bytes() {
+    struct Case {
+        s: &'static str,
+        path: &'static [u8],
+        query: &'static [u8],
+        lossy: &'static str,
+    }
+
+    let new_case = |s, path, query, lossy| Case {
+        s,
+        path,
+        query,
+        lossy,
+    };
+
+    let test_vector = vec![
+        new_case("", b"", b"", ""),
+        new_case("a+b%20c", b"a+b c", b"a b c", "a b c"),
+        new_case("%FF", b"\xff", b"\xff", "\u{fffd}"),
+        new_case(
+            "a%C3%A9%C3",
+            b"a\xc3\xa9\xc3",
+            b"a\xc3\xa9\xc3",
+            "aé\u{fffd}",
+        ),
+        new_case(
+            "%00%7F%80",
+            b"\x00\x7f\x80",
+            b"\x00\x7f\x80",
+            "\u{0}\u{7f}\u{fffd}",
+        ),
+    ];
+
+    for c in test_vector {
+        let got = super::path_unescape_bytes(c.s.as_bytes()).unwrap();
+        assert_eq!(c.path, &got[..], "path_unescape_bytes({:?})", c.s);
+        assert_eq!(
+            super::path_unescape_bytes(super::path_escape_bytes(&got).as_bytes()).unwrap(),
+            got,
+            "path_escape_bytes({:?}) doesn't round trip",
+            got
+        );
+
+        let got = super::query_unescape_bytes(c.s.as_bytes()).unwrap();
+        assert_eq!(c.query, &got[..], "query_unescape_bytes({:?})", c.s);
+        assert_eq!(
+            super::query_unescape_bytes(super::query_escape_bytes(&got).as_bytes()).unwrap(),
+            got,
+            "query_escape_bytes({:?}) doesn't round trip",
+            got
+        );
+
+        let got = super::query_unescape_lossy(c.s).unwrap();
+        assert_eq!(c.lossy, got, "query_unescape_lossy({:?})", c.s);
+    }
+
+    let err = super::path_unescape_bytes(b"a%\xffb").unwrap_err();
+    assert_eq!("invalid URL escape `%\u{fffd}b`", err.to_string());
+}
+
 struct EscapeTest {
     rawurl: &'static str,
     out: Result<String, Error>,
@@ -83,11 +144,31 @@ fn unescape_test_vector() -> Vec<EscapeTest> {
         new_case("%zzzzz", Err(Error::Escape("%zz".to_string()))),
         new_case("a+b", Ok("a b")),
         new_case("a%20b", Ok("a b")),
+        new_case("%FF", Err(Error::InvalidUTF8("%FF".to_string()))),
+        new_case("%E2%98", Err(Error::InvalidUTF8("%E2%98".to_string()))),
+        new_case("%E2%98%BA", Ok("☺")),
+        new_case("%é", Err(Error::Escape("%é".to_string()))),
+        new_case("%1é", Err(Error::Escape("%1\u{fffd}".to_string()))),
     ]
 }
 
+mod data;
+mod encode_set;
+mod errors;
+#[cfg(unix)]
+mod file_path;
+mod form;
+mod idna;
 mod internal;
+mod limits;
+mod mailto;
+mod nested;
+mod ordered_values;
 mod path;
+mod pattern;
 mod query;
+mod stream;
+mod template;
 mod url;
 mod values;
+mod whatwg;
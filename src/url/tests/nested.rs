@@ -0,0 +1,179 @@
+use std::collections::BTreeMap;
+
+use super::super::errors::{Error, LimitExceeded};
+use super::super::{NestedQueryLimits, QueryValue};
+
+fn array(v: Vec<QueryValue>) -> QueryValue {
+    QueryValue::Array(v)
+}
+
+fn map(v: Vec<(&str, QueryValue)>) -> QueryValue {
+    QueryValue::Map(
+        v.into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect::<BTreeMap<_, _>>(),
+    )
+}
+
+fn string(v: &str) -> QueryValue {
+    QueryValue::String(v.to_string())
+}
+
+#[test]
+fn parse_nested_query() {
+    struct Case {
+        query: &'static str,
+        expect: QueryValue,
+        encoded: &'static str,
+    }
+
+    let new_case = |query, expect, encoded| Case {
+        query,
+        expect,
+        encoded,
+    };
+
+    let test_vector = vec![
+        new_case("", map(vec![]), ""),
+        new_case("a=1&a=2", map(vec![("a", string("2"))]), "a=2"),
+        new_case(
+            "a[]=1&a[]=2",
+            map(vec![("a", array(vec![string("1"), string("2")]))]),
+            "a[]=1&a[]=2",
+        ),
+        new_case(
+            "a%5Bb%5D=1&a[c]=x+y",
+            map(vec![(
+                "a",
+                map(vec![("b", string("1")), ("c", string("x y"))]),
+            )]),
+            "a[b]=1&a[c]=x+y",
+        ),
+        new_case(
+            "filter[status][]=open&filter[owner]=me",
+            map(vec![(
+                "filter",
+                map(vec![
+                    ("owner", string("me")),
+                    ("status", array(vec![string("open")])),
+                ]),
+            )]),
+            "filter[owner]=me&filter[status][]=open",
+        ),
+        new_case(
+            "a[][x]=1&a[][y]=2&a[][x]=3",
+            map(vec![(
+                "a",
+                array(vec![
+                    map(vec![("x", string("1")), ("y", string("2"))]),
+                    map(vec![("x", string("3"))]),
+                ]),
+            )]),
+            "a[][x]=1&a[][y]=2&a[][x]=3",
+        ),
+        new_case(
+            "a[][b][c]=1&a[][b][d]=2&a[][b][c]=3",
+            map(vec![(
+                "a",
+                array(vec![
+                    map(vec![(
+                        "b",
+                        map(vec![("c", string("1")), ("d", string("2"))]),
+                    )]),
+                    map(vec![("b", map(vec![("c", string("3"))]))]),
+                ]),
+            )]),
+            "a[][b][c]=1&a[][b][d]=2&a[][b][c]=3",
+        ),
+        new_case(
+            "a[][]=1&a[][]=2",
+            map(vec![(
+                "a",
+                array(vec![array(vec![string("1")]), array(vec![string("2")])]),
+            )]),
+            "a[][]=1&a[][]=2",
+        ),
+        new_case(
+            "a[b=1&[c]=2&d]=3&e[f]g=4&h[i[j]]=5",
+            map(vec![
+                ("a[b", string("1")),
+                ("[c]", string("2")),
+                ("d]", string("3")),
+                ("e[f]g", string("4")),
+                ("h[i[j]]", string("5")),
+            ]),
+            "%5Bc%5D=2&a%5Bb=1&d%5D=3&e%5Bf%5Dg=4&h%5Bi%5Bj%5D%5D=5",
+        ),
+        new_case(
+            "k%26[a%3Db]=%26",
+            map(vec![("k&", map(vec![("a=b", string("&"))]))]),
+            "k%26[a%3Db]=%26",
+        ),
+    ];
+
+    for c in test_vector {
+        let got = super::super::parse_nested_query(c.query, &NestedQueryLimits::default())
+            .map_err(|err| format!("parse_nested_query({:?}) failed: {}", c.query, err))
+            .unwrap();
+
+        assert_eq!(c.expect, got, "parse_nested_query({:?})", c.query);
+        assert_eq!(c.encoded, got.encode(), "encode() of {:?}", c.query);
+
+        let again = super::super::parse_nested_query(&got.encode(), &NestedQueryLimits::default());
+        assert_eq!(got, again.unwrap(), "round trip of {:?}", c.query);
+    }
+}
+
+#[test]
+fn parse_nested_query_errors() {
+    let limits = NestedQueryLimits {
+        max_depth: 2,
+        max_params: 3,
+    };
+
+    let test_vector = vec![
+        (
+            "a=1&a[b]=2",
+            r#"net/url: conflicting types of values for query key "a[b]""#,
+        ),
+        (
+            "a[b]=1&a=2",
+            r#"net/url: conflicting types of values for query key "a""#,
+        ),
+        (
+            "a[]=1&a[b]=2",
+            r#"net/url: conflicting types of values for query key "a[b]""#,
+        ),
+        ("a=%zz", "invalid URL escape `%zz`"),
+    ];
+
+    for (query, expect) in test_vector {
+        match super::super::parse_nested_query(query, &limits) {
+            Ok(v) => panic!("parse_nested_query({:?}) = {:?}, want error", query, v),
+            Err(err) => assert_eq!(expect, err.to_string(), "parse_nested_query({:?})", query),
+        }
+    }
+
+    let test_vector = vec![
+        (
+            "a[b][c][d]=1",
+            LimitExceeded::KeyDepth {
+                key: "a[b][c][d]".to_string(),
+                limit: 2,
+            },
+        ),
+        ("a=1&b=2&c=3&d=4", LimitExceeded::QueryPairs(3)),
+    ];
+
+    for (query, expect) in test_vector {
+        match super::super::parse_nested_query(query, &limits) {
+            Err(Error::Limit(err)) => assert_eq!(expect, err, "parse_nested_query({:?})", query),
+            v => panic!(
+                "parse_nested_query({:?}) = {:?}, want {:?}",
+                query, v, expect
+            ),
+        }
+    }
+
+    assert!(super::super::parse_nested_query("a[b][c]=1&b=2&c=3", &limits).is_ok());
+}
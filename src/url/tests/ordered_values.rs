@@ -0,0 +1,92 @@
+use super::super::{OrderedValues, Values};
+
+#[test]
+fn add_get_set_del() {
+    let mut v = OrderedValues::default();
+
+    v.add("b", "1");
+    v.add("a", "2");
+    v.add("b", "3");
+    v.add("c", "4");
+    v.add("b", "5");
+
+    assert_eq!(Some("1"), v.get("b"), "get('b') failed");
+    assert_eq!(vec!["1", "3", "5"], v.get_all("b"), "get_all('b') failed");
+    assert!(v.get("B").is_none(), "get('B') should return no values");
+    assert!(
+        v.get_all("x").is_empty(),
+        "get_all('x') should return no values"
+    );
+
+    v.set("b", "x");
+    assert_eq!(
+        "b=x&a=2&c=4",
+        v.encode(),
+        "set('b') should keep the first position"
+    );
+
+    v.set("d", "y");
+    assert_eq!("b=x&a=2&c=4&d=y", v.encode(), "set('d') should append");
+
+    v.del("a");
+    assert_eq!("b=x&c=4&d=y", v.encode(), "del('a') failed");
+
+    let v: Values = v.into();
+    assert_eq!("b=x&c=4&d=y", v.encode());
+}
+
+#[test]
+fn parse_query_ordered() {
+    struct Case {
+        query: &'static str,
+        expect: Vec<(&'static str, &'static str)>,
+        encoded: &'static str,
+        ok: bool,
+    }
+
+    let new_case = |query, expect, encoded, ok| Case {
+        query,
+        expect,
+        encoded,
+        ok,
+    };
+
+    let test_vector = vec![
+        new_case("", vec![], "", true),
+        new_case("a=1", vec![("a", "1")], "a=1", true),
+        new_case(
+            "z=1&a=2&z=3",
+            vec![("z", "1"), ("a", "2"), ("z", "3")],
+            "z=1&a=2&z=3",
+            true,
+        ),
+        new_case(
+            "sig=x%2By&ts=1+2;empty&&=v",
+            vec![("sig", "x+y"), ("ts", "1 2"), ("empty", ""), ("", "v")],
+            "sig=x%2By&ts=1+2&empty=&=v",
+            true,
+        ),
+        new_case(
+            "a=%zz&b=2&%=c&d=4",
+            vec![("b", "2"), ("d", "4")],
+            "b=2&d=4",
+            false,
+        ),
+    ];
+
+    for c in test_vector {
+        let (got, ok) = match super::super::parse_query_ordered(c.query) {
+            Ok(v) => (v, true),
+            Err((v, _)) => (v, false),
+        };
+
+        let expect = c
+            .expect
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(expect, got.0, "parse_query_ordered({:?})", c.query);
+        assert_eq!(c.ok, ok, "parse_query_ordered({:?}) error", c.query);
+        assert_eq!(c.encoded, got.encode(), "encode() of {:?}", c.query);
+    }
+}
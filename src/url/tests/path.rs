@@ -33,7 +33,7 @@ fn path_escape() {
             c.s, got, c.expect
         );
 
-        let roundtrip = super::super::path_unescape(got.as_str()).unwrap();
+        let roundtrip = super::super::path_unescape(&got).unwrap();
         assert_eq!(
             c.s, roundtrip,
             "path_unescape({})={}, expect {}",
@@ -0,0 +1,228 @@
+use super::super::URLPattern;
+
+#[test]
+fn url_pattern() {
+    struct Case {
+        pattern: &'static str,
+        rawurl: &'static str,
+        groups: Option<Vec<(&'static str, &'static str)>>,
+    }
+
+    let new_case = |pattern, rawurl, groups| Case {
+        pattern,
+        rawurl,
+        groups,
+    };
+
+    let test_vector = vec![
+        // scheme
+        new_case("https://example.com/", "https://example.com/", Some(vec![])),
+        new_case("HTTPS://Example.COM/", "https://example.com", Some(vec![])),
+        new_case("https://example.com/", "http://example.com/", None),
+        new_case("*://example.com/", "ftp://example.com/", Some(vec![])),
+        // host
+        new_case(
+            "https://*.example.com/",
+            "https://a.b.example.com/",
+            Some(vec![("0", "a.b")]),
+        ),
+        new_case("https://*.example.com/", "https://example.com/", None),
+        new_case("https://*.example.com/", "https://badexample.com/", None),
+        new_case(
+            "https://:tenant.example.com/",
+            "https://Acme.Example.com/",
+            Some(vec![("tenant", "acme")]),
+        ),
+        new_case(
+            "https://:tenant.example.com/",
+            "https://a.b.example.com/",
+            None,
+        ),
+        new_case(
+            "https://api.*.example.com/",
+            "https://api.eu.example.com/",
+            Some(vec![("0", "eu")]),
+        ),
+        new_case(
+            "https://api.*.example.com/",
+            "https://api.a.b.example.com/",
+            None,
+        ),
+        new_case("https://*/", "https://anything.at.all/", Some(vec![])),
+        new_case(
+            "https://api.example.com/",
+            "https://api.example.com./",
+            Some(vec![]),
+        ),
+        new_case(
+            "https://*.example.com/",
+            "https://a.example.com.:443/",
+            Some(vec![("0", "a")]),
+        ),
+        new_case("https://*.example.com/", "https://example.com./", None),
+        new_case(
+            "https://api.example.com/",
+            "https://api.example.com../",
+            None,
+        ),
+        new_case("http://[::1]/", "http://[::1]:80/", Some(vec![])),
+        new_case("http://127.0.0.1/", "http://127.0.0.1/", Some(vec![])),
+        // port
+        new_case(
+            "https://example.com/",
+            "https://example.com:443/",
+            Some(vec![]),
+        ),
+        new_case("https://example.com/", "https://example.com:8443/", None),
+        new_case(
+            "https://example.com:443/",
+            "https://example.com/",
+            Some(vec![]),
+        ),
+        new_case(
+            "https://example.com:8443/",
+            "https://example.com:8443/",
+            Some(vec![]),
+        ),
+        new_case("https://example.com:8443/", "https://example.com/", None),
+        new_case(
+            "https://example.com:*/",
+            "https://example.com:1/",
+            Some(vec![]),
+        ),
+        new_case("foo://example.com/", "foo://example.com:1/", None),
+        // path
+        new_case(
+            "https://example.com/api/:version/*",
+            "https://example.com/api/v1/a/b%2Fc/",
+            Some(vec![("0", "a/b/c/"), ("version", "v1")]),
+        ),
+        new_case(
+            "https://example.com/api/:version/*",
+            "https://example.com/api/v1",
+            Some(vec![("0", ""), ("version", "v1")]),
+        ),
+        new_case(
+            "https://example.com/api/:version/*",
+            "https://example.com/api/",
+            None,
+        ),
+        new_case(
+            "https://example.com/api/:version/*",
+            "https://example.com/api",
+            None,
+        ),
+        new_case(
+            "/files/*/:name",
+            "http://x/files/a/b/c.txt",
+            Some(vec![("0", "a/b"), ("name", "c.txt")]),
+        ),
+        new_case("/files/*/:name", "http://x/files/", None),
+        new_case(
+            "/files/*/:name",
+            "http://x/files/c.txt",
+            Some(vec![("0", ""), ("name", "c.txt")]),
+        ),
+        new_case(
+            "/:user/*/edit/:id",
+            "/bob/a/edit/b/edit/7",
+            Some(vec![("0", "a/edit/b"), ("id", "7"), ("user", "bob")]),
+        ),
+        new_case("/:user/*/edit/:id", "/bob/edit", None),
+        new_case("/*", "/", Some(vec![("0", "")])),
+        new_case("/a%20b/:c", "/a b/%C3%A9", Some(vec![("c", "é")])),
+        new_case("/a/", "/a", None),
+        new_case("/a", "/a/", None),
+        new_case("/", "https://example.com", Some(vec![])),
+        new_case("/*", "mailto:a@example.com", None),
+        // query
+        new_case(
+            "/search?q=:q&lang=en",
+            "/search?lang=fr&lang=en&q=a+b&page=2",
+            Some(vec![("q", "a b")]),
+        ),
+        new_case("/search?q=:q&lang=en", "/search?q=x&lang=fr", None),
+        new_case("/search?q=:q", "/search", None),
+        new_case("/search?q=*", "/search?q=", Some(vec![("0", "")])),
+        new_case("/search?*", "/search", Some(vec![])),
+        // fragment
+        new_case(
+            "/doc#:section",
+            "/doc#intro",
+            Some(vec![("section", "intro")]),
+        ),
+        new_case("/doc#intro", "/doc#Intro", None),
+        new_case("/doc#*", "/doc", Some(vec![])),
+        new_case("/doc", "/doc#x", Some(vec![])),
+    ];
+
+    for c in test_vector {
+        let p = match URLPattern::parse(c.pattern) {
+            Ok(v) => v,
+            Err(err) => panic!("parse({:?}) unexpected error: {}", c.pattern, err),
+        };
+        assert_eq!(c.pattern, p.to_string());
+
+        let u = super::super::parse(c.rawurl).unwrap();
+        let got = p.captures(&u).map(|m| {
+            m.groups()
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<Vec<_>>()
+        });
+        let expect = c.groups.as_ref().map(|v| {
+            v.iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(expect, got, "{:?}.captures({:?})", c.pattern, c.rawurl);
+        assert_eq!(c.groups.is_some(), p.matches(&u));
+    }
+}
+
+#[test]
+fn url_pattern_errors() {
+    let test_vector = vec![
+        ("example.com/a", "missing scheme"),
+        ("ht tp://example.com/", "invalid scheme"),
+        ("https:///a", "empty host"),
+        ("https://a..com/", "empty host label"),
+        ("https://me@example.com/", "userinfo in pattern"),
+        ("https://example.com:/", "empty port"),
+        ("https://example.com:http/", "invalid port"),
+        ("https://[::1/", "missing ']' in host"),
+        ("/:a/:a", r#"duplicate parameter name "a""#),
+        ("/:/", r#"invalid parameter name """#),
+        ("/:a-b", r#"invalid parameter name "a-b""#),
+        ("/a%zz", "invalid URL escape `%zz`"),
+        ("/*/a/*", "more than one wildcard in path"),
+        (
+            "https://*.example.com/*/*",
+            "more than one wildcard in path",
+        ),
+    ];
+
+    for (pattern, desc) in test_vector {
+        let expect = format!("net/url: invalid URL pattern {:?}: {}", pattern, desc);
+        match URLPattern::parse(pattern) {
+            Ok(v) => panic!("parse({:?}) = {:?}, want error", pattern, v),
+            Err(err) => assert_eq!(expect, err.to_string(), "parse({:?})", pattern),
+        }
+    }
+}
+
+#[test]
+fn url_pattern_long_path() {
+    // A path of many segments is matched in a single pass, however the
+    // segments around the wildcard are laid out.
+    let segments = vec!["a"; 100_000];
+    let u = super::super::parse(&format!("/{}/b", segments.join("/"))).unwrap();
+
+    let p = URLPattern::parse("/a/*/a/:last").unwrap();
+    let m = p.captures(&u).unwrap();
+    assert_eq!(Some("b"), m.get("last"));
+    assert_eq!(Some(segments[2..].join("/").as_str()), m.get("0"));
+
+    let p = URLPattern::parse("/a/*/a/c").unwrap();
+    assert!(!p.matches(&u));
+}
@@ -31,7 +31,7 @@ fn query_escape() {
             c.s, got, c.expect
         );
 
-        let roundtrip = super::super::query_unescape(got.as_str()).unwrap();
+        let roundtrip = super::super::query_unescape(&got).unwrap();
         assert_eq!(
             c.s, roundtrip,
             "query_unescape({})={}, expect {}",
@@ -0,0 +1,232 @@
+use std::io::{self, ErrorKind, Read, Write};
+
+use super::super::{EscapeWriter, UnescapeReader};
+
+/// OneByte reads or writes a byte at a time.
+struct OneByte<T>(T);
+
+impl<R: Read> Read for OneByte<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(1);
+        self.0.read(&mut buf[..n])
+    }
+}
+
+impl<W: Write> Write for OneByte<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = buf.len().min(1);
+        self.0.write(&buf[..n])
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Budget writes as many bytes as its budget allows, and fails once it is
+/// spent.
+struct Budget {
+    out: Vec<u8>,
+    budget: usize,
+}
+
+impl Write for Budget {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.budget == 0 {
+            return Err(io::Error::new(ErrorKind::BrokenPipe, "out of budget"));
+        }
+
+        let n = buf.len().min(self.budget);
+        self.budget -= n;
+        self.out.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn escape_writer() {
+    struct Case {
+        input: Vec<u8>,
+        path: String,
+        query: String,
+    }
+
+    let new_case = |input: &[u8], path: &str, query: &str| Case {
+        input: input.to_vec(),
+        path: path.to_string(),
+        query: query.to_string(),
+    };
+
+    let long = "a b/".repeat(5000);
+    let test_vector = vec![
+        new_case(b"", "", ""),
+        new_case(b"abc", "abc", "abc"),
+        new_case(b"a b+c/\xff", "a%20b+c%2F%FF", "a+b%2Bc%2F%FF"),
+        new_case(
+            long.as_bytes(),
+            &"a%20b%2F".repeat(5000),
+            &"a+b%2F".repeat(5000),
+        ),
+    ];
+
+    for c in test_vector {
+        for one_byte in &[false, true] {
+            let mut w = EscapeWriter::path(Vec::new());
+            if *one_byte {
+                for b in &c.input {
+                    w.write_all(&[*b]).unwrap();
+                }
+            } else {
+                w.write_all(&c.input).unwrap();
+            }
+            assert_eq!(c.path.as_bytes(), &w.into_inner()[..]);
+
+            let mut w = EscapeWriter::query(OneByte(Vec::new()));
+            w.write_all(&c.input).unwrap();
+            w.flush().unwrap();
+            assert_eq!(c.query.as_bytes(), &(w.into_inner().0)[..]);
+        }
+    }
+}
+
+#[test]
+fn escape_writer_partial() {
+    struct Case {
+        budget: usize,
+        written: Option<usize>,
+        out: &'static str,
+    }
+
+    let new_case = |budget, written, out| Case {
+        budget,
+        written,
+        out,
+    };
+
+    let input = b"a b/c";
+    let test_vector = vec![
+        new_case(0, None, ""),
+        new_case(1, Some(1), "a"),
+        new_case(2, Some(2), "a%"),
+        new_case(3, Some(2), "a%2"),
+        new_case(4, Some(2), "a%20"),
+        new_case(8, Some(4), "a%20b%2F"),
+        new_case(9, Some(5), "a%20b%2Fc"),
+    ];
+
+    for c in test_vector {
+        let mut w = EscapeWriter::path(Budget {
+            out: Vec::new(),
+            budget: c.budget,
+        });
+
+        let got = w.write(input);
+        assert_eq!(
+            c.out.as_bytes(),
+            &w.get_ref().out[..],
+            "budget {}",
+            c.budget
+        );
+
+        let n = match (got, c.written) {
+            (Err(_), None) => 0,
+            (Ok(n), Some(v)) if n == v => n,
+            (got, _) => panic!(
+                "budget {}: write() = {:?}, want {:?}",
+                c.budget, got, c.written
+            ),
+        };
+
+        if n < input.len() {
+            assert!(
+                w.write(&input[n..]).is_err(),
+                "budget {}: error not returned",
+                c.budget
+            );
+        }
+
+        w.get_mut().budget = usize::MAX;
+        w.write_all(&input[n..]).unwrap();
+        w.flush().unwrap();
+        assert_eq!(b"a%20b%2Fc", &w.into_inner().out[..], "budget {}", c.budget);
+    }
+}
+
+#[test]
+fn unescape_reader() {
+    struct Case {
+        input: &'static [u8],
+        path: Result<&'static [u8], &'static str>,
+        query: Result<&'static [u8], &'static str>,
+    }
+
+    let new_case = |input, path, query| Case { input, path, query };
+
+    let test_vector = vec![
+        new_case(b"", Ok(b""), Ok(b"")),
+        new_case(b"abc", Ok(b"abc"), Ok(b"abc")),
+        new_case(b"a+b%20c%2F%ff", Ok(b"a+b c/\xff"), Ok(b"a b c/\xff")),
+        new_case(b"%41%4a%4A", Ok(b"AJJ"), Ok(b"AJJ")),
+        new_case(b"ab%", Err("%"), Err("%")),
+        new_case(b"ab%4", Err("%4"), Err("%4")),
+        new_case(b"ab%zz", Err("%zz"), Err("%zz")),
+        new_case(b"%1\xff", Err("%1\u{fffd}"), Err("%1\u{fffd}")),
+    ];
+
+    let read_all = |r: &mut dyn Read| -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        match r.read_to_end(&mut out) {
+            Ok(_) => Ok(out),
+            Err(err) => {
+                assert_eq!(ErrorKind::InvalidData, err.kind(), "{}", err);
+                Err(err.to_string())
+            }
+        }
+    };
+
+    for c in test_vector {
+        let expect = |v: Result<&[u8], &str>| -> Result<Vec<u8>, String> {
+            v.map(|v| v.to_vec())
+                .map_err(|v| format!("invalid URL escape `{}`", v))
+        };
+
+        let got = read_all(&mut UnescapeReader::path(c.input));
+        assert_eq!(expect(c.path), got, "path({:?})", c.input);
+        let got = read_all(&mut UnescapeReader::path(OneByte(c.input)));
+        assert_eq!(expect(c.path), got, "path({:?}) a byte at a time", c.input);
+
+        let got = read_all(&mut UnescapeReader::query(c.input));
+        assert_eq!(expect(c.query), got, "query({:?})", c.input);
+        let got = read_all(&mut UnescapeReader::query(OneByte(c.input)));
+        assert_eq!(
+            expect(c.query),
+            got,
+            "query({:?}) a byte at a time",
+            c.input
+        );
+    }
+
+    // the bytes before a malformed escape are read first
+    let mut r = UnescapeReader::path(&b"a%41%zz"[..]);
+    let mut buf = [0; 8];
+    assert_eq!(2, r.read(&mut buf).unwrap());
+    assert_eq!(b"aA", &buf[..2]);
+    assert!(r.read(&mut buf).is_err());
+
+    // a long input spanning several fills, read through small buffers
+    let input = "%E2%98%BA+".repeat(5000);
+    let mut r = UnescapeReader::query(input.as_bytes());
+    let mut out = Vec::new();
+    let mut buf = [0; 7];
+    loop {
+        match r.read(&mut buf).unwrap() {
+            0 => break,
+            n => out.extend_from_slice(&buf[..n]),
+        }
+    }
+    assert_eq!("☺ ".repeat(5000).as_bytes(), &out[..]);
+}
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+
+use super::super::{TemplateValue, UriTemplate};
+
+/// rfc6570_vars returns the variables of the examples of RFC 6570, Sections
+/// 1.2 and 3.2.
+fn rfc6570_vars() -> HashMap<String, TemplateValue> {
+    let mut out = HashMap::new();
+    let mut set = |k: &str, v: TemplateValue| out.insert(k.to_string(), v);
+
+    set("count", vec!["one", "two", "three"].into());
+    set("dom", vec!["example", "com"].into());
+    set("dub", "me/too".into());
+    set("hello", "Hello World!".into());
+    set("half", "50%".into());
+    set("var", "value".into());
+    set("who", "fred".into());
+    set("base", "http://example.com/home/".into());
+    set("path", "/foo/bar".into());
+    set("list", vec!["red", "green", "blue"].into());
+    set(
+        "keys",
+        vec![("semi", ";"), ("dot", "."), ("comma", ",")].into(),
+    );
+    set("v", "6".into());
+    set("x", "1024".into());
+    set("y", "768".into());
+    set("empty", "".into());
+    set("empty_keys", TemplateValue::Map(vec![]));
+
+    out
+}
+
+#[test]
+fn expand() {
+    let test_vector = vec![
+        // Level 1, Section 1.2.
+        ("{var}", "value"),
+        ("{hello}", "Hello%20World%21"),
+        // Level 2
+        ("{+var}", "value"),
+        ("{+hello}", "Hello%20World!"),
+        ("{+path}/here", "/foo/bar/here"),
+        ("here?ref={+path}", "here?ref=/foo/bar"),
+        ("X{#var}", "X#value"),
+        ("X{#hello}", "X#Hello%20World!"),
+        // Level 3
+        ("map?{x,y}", "map?1024,768"),
+        ("{x,hello,y}", "1024,Hello%20World%21,768"),
+        ("{+x,hello,y}", "1024,Hello%20World!,768"),
+        ("{+path,x}/here", "/foo/bar,1024/here"),
+        ("{#x,hello,y}", "#1024,Hello%20World!,768"),
+        ("{#path,x}/here", "#/foo/bar,1024/here"),
+        ("X{.var}", "X.value"),
+        ("X{.x,y}", "X.1024.768"),
+        ("{/var}", "/value"),
+        ("{/var,x}/here", "/value/1024/here"),
+        ("{;x,y}", ";x=1024;y=768"),
+        ("{;x,y,empty}", ";x=1024;y=768;empty"),
+        ("{?x,y}", "?x=1024&y=768"),
+        ("{?x,y,empty}", "?x=1024&y=768&empty="),
+        ("?fixed=yes{&x}", "?fixed=yes&x=1024"),
+        ("{&x,y,empty}", "&x=1024&y=768&empty="),
+        // Level 4
+        ("{var:3}", "val"),
+        ("{var:30}", "value"),
+        ("{list}", "red,green,blue"),
+        ("{list*}", "red,green,blue"),
+        ("{keys}", "semi,%3B,dot,.,comma,%2C"),
+        ("{keys*}", "semi=%3B,dot=.,comma=%2C"),
+        ("{+path:6}/here", "/foo/b/here"),
+        ("{+list}", "red,green,blue"),
+        ("{+list*}", "red,green,blue"),
+        ("{+keys}", "semi,;,dot,.,comma,,"),
+        ("{+keys*}", "semi=;,dot=.,comma=,"),
+        ("{#path:6}/here", "#/foo/b/here"),
+        ("{#list}", "#red,green,blue"),
+        ("{#list*}", "#red,green,blue"),
+        ("{#keys}", "#semi,;,dot,.,comma,,"),
+        ("{#keys*}", "#semi=;,dot=.,comma=,"),
+        ("X{.var:3}", "X.val"),
+        ("X{.list}", "X.red,green,blue"),
+        ("X{.list*}", "X.red.green.blue"),
+        ("X{.keys}", "X.semi,%3B,dot,.,comma,%2C"),
+        ("X{.keys*}", "X.semi=%3B.dot=..comma=%2C"),
+        ("{/var:1,var}", "/v/value"),
+        ("{/list}", "/red,green,blue"),
+        ("{/list*}", "/red/green/blue"),
+        ("{/list*,path:4}", "/red/green/blue/%2Ffoo"),
+        ("{/keys}", "/semi,%3B,dot,.,comma,%2C"),
+        ("{/keys*}", "/semi=%3B/dot=./comma=%2C"),
+        ("{;hello:5}", ";hello=Hello"),
+        ("{;list}", ";list=red,green,blue"),
+        ("{;list*}", ";list=red;list=green;list=blue"),
+        ("{;keys}", ";keys=semi,%3B,dot,.,comma,%2C"),
+        ("{;keys*}", ";semi=%3B;dot=.;comma=%2C"),
+        ("{?var:3}", "?var=val"),
+        ("{?list}", "?list=red,green,blue"),
+        ("{?list*}", "?list=red&list=green&list=blue"),
+        ("{?keys}", "?keys=semi,%3B,dot,.,comma,%2C"),
+        ("{?keys*}", "?semi=%3B&dot=.&comma=%2C"),
+        ("{&var:3}", "&var=val"),
+        ("{&list}", "&list=red,green,blue"),
+        ("{&list*}", "&list=red&list=green&list=blue"),
+        ("{&keys}", "&keys=semi,%3B,dot,.,comma,%2C"),
+        ("{&keys*}", "&semi=%3B&dot=.&comma=%2C"),
+        // Section 3.2.1
+        ("{count}", "one,two,three"),
+        ("{count*}", "one,two,three"),
+        ("{/count}", "/one,two,three"),
+        ("{/count*}", "/one/two/three"),
+        ("{;count}", ";count=one,two,three"),
+        ("{;count*}", ";count=one;count=two;count=three"),
+        ("{?count}", "?count=one,two,three"),
+        ("{?count*}", "?count=one&count=two&count=three"),
+        ("{&count*}", "&count=one&count=two&count=three"),
+        // Section 3.2.2
+        ("{half}", "50%25"),
+        ("O{empty}X", "OX"),
+        ("O{undef}X", "OX"),
+        ("{x,y}", "1024,768"),
+        ("?{x,empty}", "?1024,"),
+        ("?{x,undef}", "?1024"),
+        ("?{undef,y}", "?768"),
+        // Section 3.2.3
+        ("{+half}", "50%25"),
+        ("{base}index", "http%3A%2F%2Fexample.com%2Fhome%2Findex"),
+        ("{+base}index", "http://example.com/home/index"),
+        ("O{+empty}X", "OX"),
+        ("O{+undef}X", "OX"),
+        ("up{+path}{var}/here", "up/foo/barvalue/here"),
+        // Section 3.2.4
+        ("{#half}", "#50%25"),
+        ("foo{#empty}", "foo#"),
+        ("foo{#undef}", "foo"),
+        // Section 3.2.5
+        ("{.who}", ".fred"),
+        ("{.who,who}", ".fred.fred"),
+        ("{.half,who}", ".50%25.fred"),
+        ("www{.dom*}", "www.example.com"),
+        ("X{.empty}", "X."),
+        ("X{.undef}", "X"),
+        ("X{.empty_keys}", "X"),
+        ("X{.empty_keys*}", "X"),
+        // Section 3.2.6
+        ("{/who}", "/fred"),
+        ("{/who,who}", "/fred/fred"),
+        ("{/half,who}", "/50%25/fred"),
+        ("{/who,dub}", "/fred/me%2Ftoo"),
+        ("{/var,empty}", "/value/"),
+        ("{/var,undef}", "/value"),
+        // Section 3.2.7
+        ("{;who}", ";who=fred"),
+        ("{;half}", ";half=50%25"),
+        ("{;empty}", ";empty"),
+        ("{;v,empty,who}", ";v=6;empty;who=fred"),
+        ("{;v,bar,who}", ";v=6;who=fred"),
+        ("{;x,y,undef}", ";x=1024;y=768"),
+        // Section 3.2.8
+        ("{?who}", "?who=fred"),
+        ("{?half}", "?half=50%25"),
+        ("{?x,y,undef}", "?x=1024&y=768"),
+        // Section 3.2.9
+        ("{&who}", "&who=fred"),
+        ("{&half}", "&half=50%25"),
+        // Literals keep reserved characters and %-encoded triplets.
+        ("/a/%41/é/{var}", "/a/%41/%C3%A9/value"),
+        ("", ""),
+    ];
+
+    let vars = rfc6570_vars();
+    for (template, expect) in test_vector {
+        let t = match UriTemplate::parse(template) {
+            Ok(v) => v,
+            Err(err) => panic!("parse({:?}) unexpected error: {}", template, err),
+        };
+        assert_eq!(template, t.to_string());
+
+        let got = t.expand(&vars);
+        assert_eq!(expect, got, "{:?}.expand()", template);
+        assert!(
+            super::super::parse(&got).is_ok(),
+            "{:?}.expand() = {:?} doesn't parse",
+            template,
+            got
+        );
+    }
+}
+
+#[test]
+fn expand_unicode() {
+    let mut vars = HashMap::new();
+    vars.insert("q".to_string(), TemplateValue::from("日本語 テキスト"));
+    vars.insert("a".to_string(), TemplateValue::from("a%2Fb%zz"));
+
+    let test_vector = vec![
+        ("{q:2}", "%E6%97%A5%E6%9C%AC"),
+        ("{+q:3}", "%E6%97%A5%E6%9C%AC%E8%AA%9E"),
+        (
+            "{?q}",
+            "?q=%E6%97%A5%E6%9C%AC%E8%AA%9E%20%E3%83%86%E3%82%AD%E3%82%B9%E3%83%88",
+        ),
+        ("{a}", "a%252Fb%25zz"),
+        ("{+a}", "a%2Fb%25zz"),
+        ("{#a}", "#a%2Fb%25zz"),
+    ];
+
+    for (template, expect) in test_vector {
+        let got = UriTemplate::parse(template).unwrap().expand(&vars);
+        assert_eq!(expect, got, "{:?}.expand()", template);
+    }
+}
+
+#[test]
+fn parse_errors() {
+    let test_vector = vec![
+        ("{var", "unclosed expression", 0),
+        ("/a/{b}/{c", "unclosed expression", 7),
+        ("{}", "invalid expression", 0),
+        ("{=var}", "invalid expression", 0),
+        ("{!var}", "invalid expression", 0),
+        ("{var:0}", "invalid expression", 0),
+        ("{var:10000}", "invalid expression", 0),
+        ("{var:x}", "invalid expression", 0),
+        ("{var*:3}", "invalid expression", 0),
+        ("{a..b}", "invalid expression", 0),
+        ("{.a.}", "invalid expression", 0),
+        ("{a b}", "invalid expression", 0),
+        ("{a,}", "invalid expression", 0),
+        ("x}", "invalid literal", 1),
+        ("/a b", "invalid literal", 2),
+        ("/50%/{x}", "invalid literal", 3),
+        ("/a<b>", "invalid literal", 2),
+    ];
+
+    for (template, desc, offset) in test_vector {
+        let expect = format!(
+            "net/url: {} in URI template {:?} at offset {}",
+            desc, template, offset
+        );
+        match UriTemplate::parse(template) {
+            Ok(_) => panic!("parse({:?}) should fail", template),
+            Err(err) => assert_eq!(expect, err.to_string(), "parse({:?})", template),
+        }
+    }
+
+    let t = UriTemplate::parse("{a%20b.c_1:3}{?a%20b.c_1,x*}").unwrap();
+    assert_eq!(vec!["a%20b.c_1", "x"], t.variables());
+}
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use super::super::Values;
+use super::super::{ParseQueryOptions, Semicolons, Values};
 
 #[test]
 fn encode_query() {
@@ -93,3 +93,171 @@ fn parse_query() {
         );
     }
 }
+
+#[test]
+fn parse_query_with() {
+    struct Case {
+        s: &'static str,
+        options: ParseQueryOptions,
+        expect: Vec<(&'static str, &'static str)>,
+        err: Option<&'static str>,
+    }
+
+    let new_case = |s, options, expect, err| Case {
+        s,
+        options,
+        expect,
+        err,
+    };
+
+    const SEMICOLON: &str = "net/url: invalid semicolon separator in query";
+    const ESCAPE: &str = "invalid URL escape `%zz`";
+
+    let with = |semicolons, plus_as_space, keep_empty_keys, strict| ParseQueryOptions {
+        semicolons,
+        plus_as_space,
+        keep_empty_keys,
+        strict,
+        ..Default::default()
+    };
+    let default = ParseQueryOptions::default();
+
+    let test_vector = vec![
+        // Go 1.17: https://golang.org/issue/25192
+        new_case(
+            "a=1&b=2",
+            default.clone(),
+            vec![("a", "1"), ("b", "2")],
+            None,
+        ),
+        new_case("a=1;b=2", default.clone(), vec![], Some(SEMICOLON)),
+        new_case(
+            "a;b=1&c=2",
+            default.clone(),
+            vec![("c", "2")],
+            Some(SEMICOLON),
+        ),
+        new_case(
+            "a=1&b;=2&c=%zz",
+            default.clone(),
+            vec![("a", "1")],
+            Some(SEMICOLON),
+        ),
+        new_case(
+            "a=1;b=2&c=3",
+            default.clone(),
+            vec![("c", "3")],
+            Some(SEMICOLON),
+        ),
+        new_case(
+            "a=1;b=2&c=3",
+            with(Semicolons::Split, true, true, false),
+            vec![("a", "1"), ("b", "2"), ("c", "3")],
+            None,
+        ),
+        new_case(
+            "a=1;b=2&c=3",
+            with(Semicolons::Literal, true, true, false),
+            vec![("a", "1;b=2"), ("c", "3")],
+            None,
+        ),
+        new_case(
+            "a=1+1&b+c=%2B",
+            default.clone(),
+            vec![("a", "1 1"), ("b c", "+")],
+            None,
+        ),
+        new_case(
+            "a=1+1&b+c=%2B",
+            with(Semicolons::Reject, false, true, false),
+            vec![("a", "1+1"), ("b+c", "+")],
+            None,
+        ),
+        new_case(
+            "=1&a=2&=3&&",
+            default.clone(),
+            vec![("", "1"), ("a", "2"), ("", "3")],
+            None,
+        ),
+        new_case(
+            "=1&a=2&=3",
+            with(Semicolons::Reject, true, false, false),
+            vec![("a", "2")],
+            None,
+        ),
+        new_case(
+            "a=%zz&b=1&c=%",
+            default.clone(),
+            vec![("b", "1")],
+            Some(ESCAPE),
+        ),
+        new_case(
+            "a=1&b=%zz&c=1",
+            with(Semicolons::Reject, true, true, true),
+            vec![("a", "1")],
+            Some(ESCAPE),
+        ),
+        new_case(
+            "a=1&b;&c=1",
+            with(Semicolons::Reject, true, true, true),
+            vec![("a", "1")],
+            Some(SEMICOLON),
+        ),
+        new_case(
+            "a=1,b=2|c=3&d=4",
+            ParseQueryOptions {
+                separators: vec![',', '|'],
+                ..Default::default()
+            },
+            vec![("a", "1"), ("b", "2"), ("c", "3&d=4")],
+            None,
+        ),
+    ];
+
+    for c in test_vector {
+        let (got, err) = match super::super::parse_query_ordered_with(c.s, &c.options) {
+            Ok(v) => (v, None),
+            Err((v, err)) => (v, Some(err.to_string())),
+        };
+        let got = got
+            .0
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            c.expect, got,
+            "parse_query_ordered_with({:?}, {:?})",
+            c.s, c.options
+        );
+        assert_eq!(
+            c.err.map(|v| v.to_string()),
+            err,
+            "parse_query_ordered_with({:?}, {:?})",
+            c.s,
+            c.options
+        );
+
+        let (got, err) = match super::super::parse_query_with(c.s, &c.options) {
+            Ok(v) => (v, None),
+            Err((v, err)) => (v, Some(err.to_string())),
+        };
+        let mut expect = Values::default();
+        for (k, v) in &c.expect {
+            expect.add(k.to_string(), *v);
+        }
+        assert_eq!(
+            expect.0, got.0,
+            "parse_query_with({:?}, {:?})",
+            c.s, c.options
+        );
+        assert_eq!(c.err.map(|v| v.to_string()), err);
+
+        let u = super::super::parse(&format!("http://h/?{}", c.s)).unwrap();
+        let (got, err) = match u.query_with(&c.options) {
+            Ok(v) => (v, None),
+            Err((v, err)) => (v, Some(err.to_string())),
+        };
+        assert_eq!(expect.0, got.0, "query_with({:?}, {:?})", c.s, c.options);
+        assert_eq!(c.err.map(|v| v.to_string()), err);
+    }
+}
@@ -0,0 +1,135 @@
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Component, Path, PathBuf};
+
+use super::super::errors::{self, Error};
+use super::super::internal::{self, Encoding};
+use super::URL;
+
+/// Conversions between `file:` URLs and the paths of the local filesystem.
+/// Paths are taken as bytes, so that names which aren't valid UTF-8 are
+/// %-escaped in the URL and restored from it.
+impl URL {
+    /// from_directory_path is like [from_file_path](#method.from_file_path)
+    /// but ends the URL path with a slash, so that URLs resolved against it are
+    /// within the directory.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use net::url::URL;
+    ///
+    /// let u = URL::from_directory_path("/srv/www").unwrap();
+    /// assert_eq!("file:///srv/www/", u.to_string());
+    /// assert_eq!("file:///srv/www/index.html", u.parse("index.html").unwrap().to_string());
+    /// ```
+    pub fn from_directory_path<P>(path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let mut out = Self::from_file_path(path)?;
+        if !out.path.ends_with('/') {
+            let escaped = out.escaped_path() + "/";
+            out.update_path(&escaped)?;
+        }
+
+        Ok(out)
+    }
+
+    /// from_file_path returns the `file:` URL with an empty host of the
+    /// absolute path. Its components are %-escaped as paths are, empty and
+    /// "." components being dropped. It fails if the path is relative.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::ffi::OsStr;
+    /// use std::os::unix::ffi::OsStrExt;
+    ///
+    /// use net::url::URL;
+    ///
+    /// let u = URL::from_file_path("/tmp/50% off #1.txt").unwrap();
+    /// assert_eq!("file:///tmp/50%25%20off%20%231.txt", u.to_string());
+    ///
+    /// let u = URL::from_file_path(OsStr::from_bytes(b"/tmp/caf\xe9")).unwrap();
+    /// assert_eq!("file:///tmp/caf%E9", u.to_string());
+    ///
+    /// assert!(URL::from_file_path("tmp/a.txt").is_err());
+    /// ```
+    pub fn from_file_path<P>(path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        if !path.is_absolute() {
+            let err = format!("net/url: relative file path {:?}", path);
+            return Err(errors::new_misc(err));
+        }
+
+        let mut escaped = String::new();
+        for c in path.components() {
+            match c {
+                Component::Normal(v) => {
+                    escaped.push('/');
+                    escaped.push_str(&internal::escape_bytes(v.as_bytes(), Encoding::Path));
+                }
+                Component::ParentDir => escaped.push_str("/.."),
+                Component::RootDir | Component::CurDir | Component::Prefix(_) => {}
+            }
+        }
+        if escaped.is_empty() {
+            escaped.push('/');
+        }
+
+        let mut out = URL {
+            scheme: "file".to_string(),
+            ..URL::default()
+        };
+        out.update_path(&escaped)?;
+
+        Ok(out)
+    }
+
+    /// to_file_path returns the local path of a `file:` URL, whose host must
+    /// be empty or "localhost". The path is unescaped into bytes, so that it
+    /// may name files which aren't valid UTF-8, and it fails if it holds a NUL
+    /// byte.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    ///
+    /// let u = net::url::parse("file://localhost/etc/fstab").unwrap();
+    /// assert_eq!(PathBuf::from("/etc/fstab"), u.to_file_path().unwrap());
+    ///
+    /// let u = net::url::parse("file:///tmp/50%25%20off").unwrap();
+    /// assert_eq!(PathBuf::from("/tmp/50% off"), u.to_file_path().unwrap());
+    ///
+    /// let u = net::url::parse("file://example.com/etc/fstab").unwrap();
+    /// assert!(u.to_file_path().is_err());
+    /// ```
+    pub fn to_file_path(&self) -> Result<PathBuf, Error> {
+        if self.scheme != "file" {
+            let err = format!("net/url: scheme {:?} is not file", self.scheme);
+            return Err(errors::new_misc(err));
+        }
+
+        if !self.host.is_empty() && !self.host.eq_ignore_ascii_case("localhost") {
+            let err = format!("net/url: file URL with non-local host {:?}", self.host);
+            return Err(errors::new_misc(err));
+        }
+
+        if !self.opaque.is_empty() || !self.path.starts_with('/') {
+            return Err(errors::new_misc("net/url: file URL without absolute path"));
+        }
+
+        let escaped = self.escaped_path();
+        let path = internal::unescape_bytes(escaped.as_bytes(), Encoding::Path)?;
+        if path.contains(&0) {
+            return Err(errors::new_misc("net/url: NUL byte in file URL path"));
+        }
+
+        Ok(PathBuf::from(OsStr::from_bytes(&path)))
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+use super::super::errors::{self, Error};
+use super::super::internal::{self, Encoding};
+
+/// Host is the parsed form of the host part of a URL, without the port.
+///
+/// # Example
+///
+/// ```
+/// use net::url::{self, Host};
+///
+/// let u = url::parse("http://[fe80::1%25en0]:8080/").unwrap();
+///
+/// let host = u.host_kind().unwrap();
+/// assert_eq!(
+///     Host::Ipv6 {
+///         addr: "fe80::1".parse().unwrap(),
+///         zone: Some("en0".to_string()),
+///     },
+///     host
+/// );
+/// assert_eq!("[fe80::1%25en0]", host.to_string());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Host {
+    /// a registered name, unescaped
+    Domain(String),
+    /// a dotted-decimal IPv4 address
+    Ipv4(Ipv4Addr),
+    /// a bracketed IPv6 address, with the RFC 6874 zone identifier unescaped
+    Ipv6 {
+        addr: Ipv6Addr,
+        zone: Option<String>,
+    },
+}
+
+impl Host {
+    /// from_host classifies an unescaped host[:port], as stored in the `host`
+    /// field of a URL, and returns it with the port.
+    pub(super) fn from_host(host: &str) -> Result<(Self, &str), Error> {
+        let literal = match host.strip_prefix('[') {
+            Some(v) => v,
+            None => {
+                let (hostname, port) = super::split_host_port(host);
+                let out = match hostname.parse::<Ipv4Addr>() {
+                    Ok(v) => Host::Ipv4(v),
+                    Err(_) => Host::Domain(hostname.to_string()),
+                };
+                return Ok((out, port));
+            }
+        };
+
+        let i = literal
+            .rfind(']')
+            .ok_or_else(|| errors::new_misc("missing ']' in host"))?;
+        let colon_port = literal.get((i + 1)..).unwrap_or_default();
+        if !super::valid_optional_port(colon_port) {
+            let err = format!("invalid port {:?} after host", colon_port);
+            return Err(errors::new_misc(err));
+        }
+
+        let literal = &literal[..i];
+        // An empty zone identifier, as in "[::1%25]", is accepted by parse and so
+        // kept as Some("").
+        let (addr, zone) = match literal.find('%') {
+            Some(i) => (&literal[..i], Some(literal[(i + 1)..].to_string())),
+            None => (literal, None),
+        };
+
+        match addr.parse::<Ipv6Addr>() {
+            Ok(addr) => Ok((
+                Host::Ipv6 { addr, zone },
+                colon_port.get(1..).unwrap_or_default(),
+            )),
+            Err(_) => {
+                let err = format!("invalid IPv6 address {} in host", addr);
+                Err(errors::new_misc(err))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Host {
+    /// fmt writes the host in the escaped form used by URLs, enclosing IPv6
+    /// addresses in square brackets and introducing zone identifiers with "%25".
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Host::Domain(v) => write!(f, "{}", internal::escape(v, Encoding::Host)),
+            Host::Ipv4(v) => write!(f, "{}", v),
+            Host::Ipv6 { addr, zone: None } => write!(f, "[{}]", addr),
+            Host::Ipv6 {
+                addr,
+                zone: Some(zone),
+            } => write!(f, "[{}%25{}]", addr, internal::escape(zone, Encoding::Zone)),
+        }
+    }
+}
+
+impl FromStr for Host {
+    type Err = Error;
+
+    /// from_str parses an escaped host without port, such as "example.com",
+    /// "192.168.0.1" or "[fe80::1%25en0]".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let host = super::parse_host(s)?;
+        if host.is_empty() {
+            return Err(errors::new_misc("empty host"));
+        }
+
+        let (out, port) = Host::from_host(&host)?;
+        if !port.is_empty() || (!host.starts_with('[') && host.contains(':')) {
+            let err = format!("unexpected port in host {}", s);
+            return Err(errors::new_misc(err));
+        }
+
+        Ok(out)
+    }
+}
+
+impl From<IpAddr> for Host {
+    fn from(addr: IpAddr) -> Self {
+        match addr {
+            IpAddr::V4(v) => Host::Ipv4(v),
+            IpAddr::V6(v) => Host::Ipv6 {
+                addr: v,
+                zone: None,
+            },
+        }
+    }
+}
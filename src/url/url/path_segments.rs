@@ -0,0 +1,104 @@
+use super::super::internal::{self, Encoding};
+use super::URL;
+
+/// PathSegmentsMut edits the path of a URL segment by segment, as returned by
+/// [path_segments_mut](struct.URL.html#method.path_segments_mut). Segments are
+/// given unescaped and escaped as by [path_escape](fn.path_escape.html), so a
+/// '/' inside a segment doesn't split it. Every change is applied to the
+/// `path` and `raw_path` of the URL at once.
+///
+/// A path starting with an empty segment in a URL without scheme nor authority
+/// is written with a leading "." segment, as the WHATWG URL Standard does, so
+/// that the empty segment reads back neither as an authority nor as the root.
+#[derive(Debug)]
+pub struct PathSegmentsMut<'a> {
+    url: &'a mut URL,
+    absolute: bool,
+    // escaped segments
+    segments: Vec<String>,
+}
+
+impl<'a> PathSegmentsMut<'a> {
+    pub(super) fn new(url: &'a mut URL) -> Self {
+        let escaped = url.escaped_path();
+        let absolute = escaped.starts_with('/')
+            || !url.scheme.is_empty()
+            || !url.host.is_empty()
+            || url.user.is_some();
+
+        Self {
+            segments: split(&escaped).map(|v| v.to_string()).collect(),
+            url,
+            absolute,
+        }
+    }
+
+    /// clear removes all segments, leaving the path "/" if it was absolute.
+    pub fn clear(&mut self) -> &mut Self {
+        self.segments.clear();
+        self.apply()
+    }
+
+    /// extend appends the segments in order.
+    pub fn extend<I, S>(&mut self, segments: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for v in segments {
+            let v = internal::escape(v.as_ref(), Encoding::PathSegment);
+            self.segments.push(v.into_owned());
+        }
+
+        self.apply()
+    }
+
+    /// pop removes the last segment and returns it unescaped, if any.
+    pub fn pop(&mut self) -> Option<String> {
+        let v = self.segments.pop()?;
+        self.apply();
+
+        Some(unescape(&v))
+    }
+
+    /// push appends a segment. Pushing after a trailing slash, which ends the
+    /// path with an empty segment, leaves that empty segment in place.
+    pub fn push(&mut self, segment: &str) -> &mut Self {
+        self.extend(Some(segment))
+    }
+
+    fn apply(&mut self) -> &mut Self {
+        let mut path = self.segments.join("/");
+        let has_authority = !self.url.host.is_empty() || self.url.user.is_some();
+        if self.url.scheme.is_empty() && !has_authority && path.starts_with('/') {
+            path.insert_str(0, "./");
+        }
+        if self.absolute {
+            path.insert(0, '/');
+        }
+
+        // Segments were escaped, or kept as found.
+        let _ = self.url.update_path(&path);
+        self.url.opaque.clear();
+
+        self
+    }
+}
+
+/// segments returns the unescaped segments of the escaped path.
+pub(super) fn segments(escaped: &str) -> Vec<String> {
+    split(escaped).map(unescape).collect()
+}
+
+/// split splits the escaped path into its segments, without the leading '/'.
+/// The paths "" and "/" have no segments.
+fn split(escaped: &str) -> impl Iterator<Item = &str> {
+    let path = escaped.strip_prefix('/').unwrap_or(escaped);
+    path.split('/').filter(move |_| !path.is_empty())
+}
+
+fn unescape(segment: &str) -> String {
+    internal::unescape_lossy(segment, Encoding::PathSegment)
+        .map(|v| v.into_owned())
+        .unwrap_or_else(|_| segment.to_string())
+}
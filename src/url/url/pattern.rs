@@ -0,0 +1,467 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::super::errors::{self, Error};
+use super::super::internal::{self, Encoding};
+use super::URL;
+
+/// URLPattern matches URLs component by component, as used for routing and
+/// allowlists, and captures the parts matched by its named parameters and
+/// wildcards.
+///
+/// A pattern is written as a URL, "scheme://host[:port]/path[?query][#fragment]",
+/// or as a path alone, which matches any scheme and host:
+///
+/// - the scheme is matched case-insensitively, "*" matching any scheme;
+/// - the host is matched label by label, case-insensitively, ignoring the
+///   trailing dot of a fully qualified name. A ":name" label captures one label
+///   and a "*" label matches one label, or one or more labels in the first
+///   place, so that "*.example.com" matches the subdomains of example.com but
+///   not example.com itself. A host of "*" matches any host;
+/// - the port is matched after dropping the default port of the scheme, "*"
+///   matching any port;
+/// - the path is matched segment by segment once unescaped. A ":name" segment
+///   captures one non-empty segment, and a "*" segment, of which there can be
+///   only one, matches any number of segments, which it captures joined by
+///   slashes;
+/// - the query, unless left out or "*", is a list of key-value pairs which
+///   must all be found in the URL, in any order and along with others. A value
+///   of ":name" captures the first value of the key and "*" matches any value;
+/// - the fragment, unless left out or "*", matches the fragment of the URL
+///   literally, or captures it if ":name".
+///
+/// Wildcards are captured under their position among the wildcards of the
+/// pattern, counting from "0".
+///
+/// # Example
+///
+/// ```
+/// use net::url::{self, URLPattern};
+///
+/// let p: URLPattern = "https://*.example.com/api/:version/*".parse().unwrap();
+///
+/// let u = url::parse("https://eu.cdn.example.com/api/v2/users/42?x=1").unwrap();
+/// let m = p.captures(&u).unwrap();
+/// assert_eq!(Some("eu.cdn"), m.get("0"));
+/// assert_eq!(Some("v2"), m.get("version"));
+/// assert_eq!(Some("users/42"), m.get("1"));
+///
+/// assert!(!p.matches(&url::parse("https://example.com/api/v2/").unwrap()));
+/// assert!(!p.matches(&url::parse("http://a.example.com/api/v2/").unwrap()));
+/// assert!(!p.matches(&url::parse("https://a.example.com:8443/api/v2/").unwrap()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct URLPattern {
+    fragment: Option<Token>,
+    host: Option<Vec<Token>>,
+    path: Vec<Token>,
+    port: Port,
+    query: Option<Vec<(String, Token)>>,
+    raw: String,
+    scheme: Option<String>,
+}
+
+/// URLPatternMatch holds the groups captured by a
+/// [URLPattern](struct.URLPattern.html) from a URL, unescaped.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct URLPatternMatch {
+    groups: Vec<(String, String)>,
+}
+
+/// Token is a label, a path segment or a value of a pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Literal(String),
+    Param(String),
+    Wildcard(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Port {
+    Any,
+    Default,
+    Exact(String),
+}
+
+impl URLPattern {
+    /// parse compiles a pattern, as described for
+    /// [URLPattern](struct.URLPattern.html).
+    pub fn parse(pattern: &str) -> Result<Self, Error> {
+        let mut out = URLPattern {
+            fragment: None,
+            host: None,
+            path: vec![],
+            port: Port::Any,
+            query: None,
+            raw: pattern.to_string(),
+            scheme: None,
+        };
+        let mut compiler = Compiler::new(pattern);
+
+        let (rest, fragment) = match pattern.find('#') {
+            Some(i) => (&pattern[..i], Some(&pattern[(i + 1)..])),
+            None => (pattern, None),
+        };
+        let (rest, query) = match rest.find('?') {
+            Some(i) => (&rest[..i], Some(&rest[(i + 1)..])),
+            None => (rest, None),
+        };
+
+        let path = if rest.starts_with('/') {
+            rest
+        } else {
+            let i = rest
+                .find("://")
+                .ok_or_else(|| compiler.error("missing scheme"))?;
+            out.scheme = compiler.scheme(&rest[..i])?;
+
+            let rest = &rest[(i + 3)..];
+            let (authority, path) = match rest.find('/') {
+                Some(i) => (&rest[..i], &rest[i..]),
+                None => (rest, "/"),
+            };
+            let (host, port) = compiler.authority(authority)?;
+            out.host = host;
+            out.port = port;
+
+            path
+        };
+
+        for segment in path[1..].split('/') {
+            let token = compiler.token(segment, Encoding::Path)?;
+            if let Token::Wildcard(_) = token {
+                if out.path.iter().any(|v| matches!(v, Token::Wildcard(_))) {
+                    return Err(compiler.error("more than one wildcard in path"));
+                }
+            }
+            out.path.push(token);
+        }
+
+        if let Some(query) = query.filter(|&v| v != "*") {
+            let mut pairs = vec![];
+            for kv in query.split('&').filter(|v| !v.is_empty()) {
+                let (k, v) = super::split(kv, '=', true);
+                let k = compiler.unescape(k, Encoding::QueryComponent)?;
+                pairs.push((k, compiler.token(v, Encoding::QueryComponent)?));
+            }
+            out.query = Some(pairs);
+        }
+
+        if let Some(fragment) = fragment.filter(|&v| v != "*") {
+            out.fragment = Some(compiler.token(fragment, Encoding::Fragment)?);
+        }
+
+        Ok(out)
+    }
+
+    /// as_str returns the pattern as it was parsed.
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    /// captures matches u against the pattern, returning the captured groups
+    /// if it matches.
+    pub fn captures(&self, u: &URL) -> Option<URLPatternMatch> {
+        let mut out = URLPatternMatch::default();
+
+        if let Some(scheme) = &self.scheme {
+            if !u.scheme.eq_ignore_ascii_case(scheme) {
+                return None;
+            }
+        }
+
+        if let Some(host) = &self.host {
+            let hostname = u.hostname().to_ascii_lowercase();
+            let hostname = hostname.strip_suffix('.').unwrap_or(&hostname);
+            let labels = hostname.split('.').collect::<Vec<_>>();
+            if !match_labels(host, &labels, &mut out) {
+                return None;
+            }
+        }
+
+        let default_port = super::default_port(&u.scheme).unwrap_or_default();
+        let port = match u.port() {
+            "" => default_port,
+            v => v,
+        };
+        match &self.port {
+            Port::Any => {}
+            Port::Default if port == default_port => {}
+            Port::Exact(v) if v == port => {}
+            _ => return None,
+        }
+
+        let escaped = u.escaped_path();
+        if !u.opaque.is_empty() || !(escaped.is_empty() || escaped.starts_with('/')) {
+            return None;
+        }
+        let segments = match escaped.get(1..) {
+            Some(v) => v
+                .split('/')
+                .map(|v| internal::unescape_lossy(v, Encoding::Path).ok())
+                .collect::<Option<Vec<_>>>()?,
+            None => vec!["".into()],
+        };
+        let segments = segments.iter().map(|v| v.as_ref()).collect::<Vec<_>>();
+        if !match_segments(&self.path, &segments, &mut out) {
+            return None;
+        }
+
+        if let Some(pairs) = &self.query {
+            let query = u.query();
+            for (k, v) in pairs {
+                let values = query.0.get(k)?;
+                let found = match v {
+                    Token::Literal(v) => values.contains(v),
+                    _ => !values.is_empty(),
+                };
+                if !found {
+                    return None;
+                }
+                capture(v, &values[0], &mut out);
+            }
+        }
+
+        if let Some(fragment) = &self.fragment {
+            match fragment {
+                Token::Literal(v) if v != &u.fragment => return None,
+                v => capture(v, &u.fragment, &mut out),
+            }
+        }
+
+        out.groups.sort_by(|a, b| a.0.cmp(&b.0));
+        Some(out)
+    }
+
+    /// matches reports whether u matches the pattern.
+    pub fn matches(&self, u: &URL) -> bool {
+        self.captures(u).is_some()
+    }
+}
+
+impl fmt::Display for URLPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+impl FromStr for URLPattern {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl URLPatternMatch {
+    /// get returns the group captured under name, which is a parameter name
+    /// or the position of a wildcard.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.groups
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// groups returns the captured groups, sorted by name.
+    pub fn groups(&self) -> &[(String, String)] {
+        &self.groups
+    }
+}
+
+/// Compiler keeps the state of the parsing of a pattern: the names in use and
+/// the number of wildcards so far.
+struct Compiler<'a> {
+    names: Vec<String>,
+    pattern: &'a str,
+    wildcards: usize,
+}
+
+impl<'a> Compiler<'a> {
+    fn new(pattern: &'a str) -> Self {
+        Self {
+            names: vec![],
+            pattern,
+            wildcards: 0,
+        }
+    }
+
+    /// authority compiles the host and port patterns. A colon starting a host
+    /// label introduces a parameter, while any other introduces the port.
+    fn authority(&mut self, authority: &str) -> Result<(Option<Vec<Token>>, Port), Error> {
+        if authority.contains('@') {
+            return Err(self.error("userinfo in pattern"));
+        }
+
+        let start = match authority.strip_prefix('[') {
+            Some(v) => v.find(']').map_or(authority.len(), |i| i + 2),
+            None => 0,
+        };
+        let b = authority.as_bytes();
+        let colon = (start.max(1)..b.len()).find(|&i| b[i] == b':' && b[i - 1] != b'.');
+
+        let (host, port) = match colon {
+            Some(i) => (&authority[..i], &authority[(i + 1)..]),
+            None => (authority, ""),
+        };
+        let port = match port {
+            "" if colon.is_some() => return Err(self.error("empty port")),
+            "" => Port::Default,
+            "*" => Port::Any,
+            v if v.bytes().all(|c| c.is_ascii_digit()) => Port::Exact(v.to_string()),
+            _ => return Err(self.error("invalid port")),
+        };
+
+        if host.is_empty() {
+            return Err(self.error("empty host"));
+        }
+        if host == "*" {
+            return Ok((None, port));
+        }
+
+        if let Some(v) = host.strip_prefix('[') {
+            let literal = v
+                .strip_suffix(']')
+                .ok_or_else(|| self.error("missing ']' in host"))?;
+            let literal = self.unescape(literal, Encoding::Host)?;
+            return Ok((
+                Some(vec![Token::Literal(literal.to_ascii_lowercase())]),
+                port,
+            ));
+        }
+
+        let mut labels = vec![];
+        for label in host.split('.') {
+            let token = match self.token(label, Encoding::Host)? {
+                Token::Literal(v) if v.is_empty() => return Err(self.error("empty host label")),
+                Token::Literal(v) => Token::Literal(v.to_ascii_lowercase()),
+                v => v,
+            };
+            labels.push(token);
+        }
+
+        Ok((Some(labels), port))
+    }
+
+    /// error reports a malformed pattern.
+    fn error(&self, desc: &str) -> Error {
+        let err = format!("net/url: invalid URL pattern {:?}: {}", self.pattern, desc);
+        errors::new_misc(err)
+    }
+
+    /// scheme compiles the scheme pattern, None matching any scheme.
+    fn scheme(&mut self, scheme: &str) -> Result<Option<String>, Error> {
+        if scheme == "*" {
+            return Ok(None);
+        }
+
+        let valid = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .bytes()
+                .all(|c| c.is_ascii_alphanumeric() || c == b'+' || c == b'-' || c == b'.');
+        if !valid {
+            return Err(self.error("invalid scheme"));
+        }
+
+        Ok(Some(scheme.to_ascii_lowercase()))
+    }
+
+    /// token compiles a label, a segment or a value: a ":name" parameter, a
+    /// "*" wildcard or an escaped literal.
+    fn token(&mut self, s: &str, mode: Encoding) -> Result<Token, Error> {
+        if s == "*" {
+            self.wildcards += 1;
+            return Ok(Token::Wildcard(self.wildcards - 1));
+        }
+
+        let name = match s.strip_prefix(':') {
+            Some(v) => v,
+            None => return Ok(Token::Literal(self.unescape(s, mode)?)),
+        };
+
+        if name.is_empty() || !name.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'_') {
+            return Err(self.error(&format!("invalid parameter name {:?}", name)));
+        }
+        if self.names.iter().any(|v| v == name) {
+            return Err(self.error(&format!("duplicate parameter name {:?}", name)));
+        }
+        self.names.push(name.to_string());
+
+        Ok(Token::Param(name.to_string()))
+    }
+
+    /// unescape unescapes a literal of the pattern.
+    fn unescape(&self, s: &str, mode: Encoding) -> Result<String, Error> {
+        match internal::unescape_lossy(s, mode) {
+            Ok(v) => Ok(v.into_owned()),
+            Err(err) => Err(self.error(&err.to_string())),
+        }
+    }
+}
+
+/// capture records the value matched by a parameter or a wildcard.
+fn capture(token: &Token, value: &str, out: &mut URLPatternMatch) {
+    match token {
+        Token::Literal(_) => {}
+        Token::Param(name) => out.groups.push((name.clone(), value.to_string())),
+        Token::Wildcard(i) => out.groups.push((i.to_string(), value.to_string())),
+    }
+}
+
+/// match_labels matches the labels of a host, a leading wildcard matching one
+/// or more labels.
+fn match_labels(pattern: &[Token], labels: &[&str], out: &mut URLPatternMatch) -> bool {
+    let (pattern, labels) = match pattern.first() {
+        Some(v @ Token::Wildcard(_)) if labels.len() >= pattern.len() => {
+            let n = labels.len() - pattern.len() + 1;
+            capture(v, &labels[..n].join("."), out);
+            (&pattern[1..], &labels[n..])
+        }
+        _ => (pattern, labels),
+    };
+
+    if pattern.len() != labels.len() {
+        return false;
+    }
+
+    for (p, &label) in pattern.iter().zip(labels) {
+        match p {
+            Token::Literal(v) if v != label => return false,
+            v => capture(v, label, out),
+        }
+    }
+
+    true
+}
+
+/// match_segments matches the unescaped segments of a path. The tokens before
+/// and after the wildcard, if any, match the first and last segments one to
+/// one, and the wildcard captures those left in between.
+fn match_segments(pattern: &[Token], segments: &[&str], out: &mut URLPatternMatch) -> bool {
+    let i = pattern.iter().position(|v| matches!(v, Token::Wildcard(_)));
+    let (head, wildcard, tail) = match i {
+        Some(i) => (&pattern[..i], Some(&pattern[i]), &pattern[(i + 1)..]),
+        None => (pattern, None, &pattern[pattern.len()..]),
+    };
+
+    let fixed = head.len() + tail.len();
+    if segments.len() < fixed || (wildcard.is_none() && segments.len() != fixed) {
+        return false;
+    }
+
+    let end = segments.len() - tail.len();
+    let tokens = head.iter().zip(&segments[..head.len()]);
+    for (p, &segment) in tokens.chain(tail.iter().zip(&segments[end..])) {
+        match p {
+            Token::Literal(v) if v == segment => {}
+            Token::Param(_) if !segment.is_empty() => capture(p, segment, out),
+            _ => return false,
+        }
+    }
+
+    if let Some(p) = wildcard {
+        capture(p, &segments[head.len()..end].join("/"), out);
+    }
+
+    true
+}
@@ -0,0 +1,332 @@
+use std::borrow::Cow;
+
+use super::super::errors::{self, Error, ParseError, ParseErrorKind};
+use super::super::internal::{self, Encoding};
+use super::super::{Limits, OrderedValues, UserInfo, Values};
+use super::URL;
+
+/// URLRef is a parsed URL borrowing its components from the input string.
+///
+/// Parsing into a URLRef applies the same validation as [parse](fn.parse.html)
+/// without allocating: every component is kept as a slice of the input in its
+/// escaped form, and is only unescaped when asked for, borrowing again if there
+/// is nothing to unescape. As for [URL](struct.URL.html), escapes decoding to
+/// invalid UTF-8 are replaced by U+FFFD. [to_url](#method.to_url) converts it
+/// into a URL, as does `URL::from`.
+///
+/// # Example
+///
+/// ```
+/// use std::borrow::Cow;
+///
+/// use net::url::URLRef;
+///
+/// let u = URLRef::parse("https://me@example.com/foo%2fbar?q=1#top").unwrap();
+///
+/// assert_eq!("https", u.scheme());
+/// assert_eq!(Cow::Borrowed("example.com"), u.host());
+/// assert_eq!("/foo%2fbar", u.escaped_path());
+/// assert_eq!(Cow::<str>::Owned("/foo/bar".to_string()), u.path());
+/// assert_eq!("q=1", u.raw_query());
+///
+/// assert_eq!(net::url::parse(u.as_str()).unwrap(), u.to_url());
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct URLRef<'a> {
+    force_query: bool,
+    fragment: &'a str,
+    host: &'a str,
+    opaque: &'a str,
+    path: &'a str,
+    raw: &'a str,
+    raw_query: &'a str,
+    scheme: &'a str,
+    star: bool,
+    user: Option<&'a str>,
+}
+
+impl<'a> URLRef<'a> {
+    /// parse parses rawurl into a URLRef borrowing from it, under the same rules
+    /// as [parse](fn.parse.html).
+    pub fn parse(rawurl: &'a str) -> Result<Self, Error> {
+        let (u, frag) = super::split(rawurl, '#', true);
+        let mut out = Self::do_parse(u, false)
+            .map_err(|err| errors::wrap("parse", u, err.with_input(rawurl).into()))?;
+        out.raw = rawurl;
+
+        if frag.is_empty() {
+            return Ok(out);
+        }
+
+        super::validate(frag, Encoding::Fragment).map_err(|err| {
+            let err = err.at(u.len() + 1).with_input(rawurl);
+            errors::wrap("parse", rawurl, err.into())
+        })?;
+        out.fragment = frag;
+
+        Ok(out)
+    }
+
+    /// parse_request_uri parses rawurl into a URLRef borrowing from it, under the
+    /// same rules as [parse_request_uri](fn.parse_request_uri.html).
+    pub fn parse_request_uri(rawurl: &'a str) -> Result<Self, Error> {
+        Self::do_parse(rawurl, true)
+            .map_err(|err| errors::wrap("parse", rawurl, err.with_input(rawurl).into()))
+    }
+
+    /// parse_with_limits is like [parse](#method.parse) but fails if rawurl
+    /// exceeds the length limits, as told by
+    /// [parse_with_limits](fn.parse_with_limits.html).
+    pub fn parse_with_limits(rawurl: &'a str, limits: &Limits) -> Result<Self, Error> {
+        limits.check_url(rawurl)?;
+
+        let out = Self::parse(rawurl)?;
+        limits
+            .check_host(&out.hostname())
+            .map_err(|err| errors::wrap("parse", rawurl, err))?;
+
+        Ok(out)
+    }
+
+    /// as_str returns the string the URLRef was parsed from.
+    pub fn as_str(&self) -> &'a str {
+        self.raw
+    }
+
+    /// escaped_fragment returns the fragment as found in the input, without '#'.
+    pub fn escaped_fragment(&self) -> &'a str {
+        self.fragment
+    }
+
+    /// escaped_path returns the path as found in the input.
+    pub fn escaped_path(&self) -> &'a str {
+        self.path
+    }
+
+    /// force_query reports whether the input ends with a '?' introducing an
+    /// empty query.
+    pub fn force_query(&self) -> bool {
+        self.force_query
+    }
+
+    /// fragment returns the unescaped fragment.
+    pub fn fragment(&self) -> Cow<'a, str> {
+        unescaped(self.fragment, Encoding::Fragment)
+    }
+
+    /// host returns the unescaped host or host:port.
+    pub fn host(&self) -> Cow<'a, str> {
+        let (host1, host2, host3) = super::check_host(self.host).expect(VALIDATED);
+        if host2.is_empty() {
+            return unescaped(host1, Encoding::Host);
+        }
+
+        let mut out = unescaped(host1, Encoding::Host).into_owned();
+        out.push_str(&unescaped(host2, Encoding::Zone));
+        out.push_str(&unescaped(host3, Encoding::Host));
+
+        Cow::Owned(out)
+    }
+
+    /// hostname returns the unescaped host, stripping any valid port number if
+    /// present. As for [URL](struct.URL.html#method.hostname), square brackets
+    /// around IPv6 literals are removed.
+    pub fn hostname(&self) -> Cow<'a, str> {
+        match self.host() {
+            Cow::Borrowed(v) => Cow::Borrowed(super::split_host_port(v).0),
+            Cow::Owned(v) => Cow::Owned(super::split_host_port(&v).0.to_string()),
+        }
+    }
+
+    /// opaque returns the encoded opaque data.
+    pub fn opaque(&self) -> &'a str {
+        self.opaque
+    }
+
+    /// password returns the unescaped password, if any.
+    pub fn password(&self) -> Option<Cow<'a, str>> {
+        let userinfo = self.user?;
+        let i = userinfo.find(':')?;
+        Some(unescaped(&userinfo[(i + 1)..], Encoding::UserPassword))
+    }
+
+    /// path returns the unescaped path.
+    pub fn path(&self) -> Cow<'a, str> {
+        if self.star {
+            return Cow::Borrowed(self.path);
+        }
+
+        unescaped(self.path, Encoding::Path)
+    }
+
+    /// port returns the port part of the host, without the leading colon.
+    pub fn port(&self) -> &'a str {
+        super::split_host_port(self.host).1
+    }
+
+    /// query parses the raw query and returns the corresponding values. It
+    /// silently discards malformed value pairs.
+    pub fn query(&self) -> Values {
+        match super::super::parse_query(self.raw_query) {
+            Ok(v) => v,
+            Err((v, _)) => v,
+        }
+    }
+
+    /// query_ordered is like [query](#method.query) but keeps the values in the
+    /// order found in the raw query.
+    pub fn query_ordered(&self) -> OrderedValues {
+        match super::super::parse_query_ordered(self.raw_query) {
+            Ok(v) => v,
+            Err((v, _)) => v,
+        }
+    }
+
+    /// raw_query returns the encoded query values, without '?'.
+    pub fn raw_query(&self) -> &'a str {
+        self.raw_query
+    }
+
+    /// scheme returns the scheme in lower case, borrowing it if it is already.
+    pub fn scheme(&self) -> Cow<'a, str> {
+        if self.scheme.bytes().any(|c| c.is_ascii_uppercase()) {
+            Cow::Owned(self.scheme.to_ascii_lowercase())
+        } else {
+            Cow::Borrowed(self.scheme)
+        }
+    }
+
+    /// to_url converts the URLRef into a URL equal to the one returned by
+    /// [parse](fn.parse.html) on the same input.
+    pub fn to_url(&self) -> URL {
+        let mut out = URL {
+            force_query: self.force_query,
+            host: self.host().into_owned(),
+            opaque: self.opaque.to_string(),
+            raw_query: self.raw_query.to_string(),
+            scheme: self.scheme().into_owned(),
+            user: self.user(),
+            ..URL::default()
+        };
+
+        // The escapes of both were validated on parsing.
+        if self.star {
+            out.path = self.path.to_string();
+        } else {
+            let _ = out.update_path(self.path);
+        }
+        if !self.fragment.is_empty() {
+            let _ = out.update_fragment(self.fragment);
+        }
+
+        out
+    }
+
+    /// user returns the username and password information, if any.
+    pub fn user(&self) -> Option<UserInfo> {
+        self.user
+            .map(|v| super::parse_userinfo(v).expect(VALIDATED))
+    }
+
+    /// username returns the unescaped username, if any.
+    pub fn username(&self) -> Option<Cow<'a, str>> {
+        let (username, _) = super::split(self.user?, ':', true);
+        Some(unescaped(username, Encoding::UserPassword))
+    }
+
+    /// do_parse parses a URL from a string in one of two contexts. If
+    /// viaRequest is true, the URL is assumed to have arrived via an HTTP request,
+    /// in which case only absolute URLs or path-absolute relative URLs are allowed.
+    /// If viaRequest is false, all forms of relative URLs are allowed.
+    fn do_parse(rawurl: &'a str, via_request: bool) -> Result<Self, ParseError> {
+        if let Some(i) = rawurl.bytes().position(|c| c < b' ' || c == 0x7f) {
+            return Err(ParseError::new(ParseErrorKind::ControlCharacter, i));
+        }
+
+        if rawurl.is_empty() && via_request {
+            return Err(ParseError::new(ParseErrorKind::EmptyURL, 0));
+        }
+
+        let mut out = URLRef {
+            raw: rawurl,
+            ..URLRef::default()
+        };
+
+        if rawurl == "*" {
+            out.path = rawurl;
+            out.star = true;
+            return Ok(out);
+        }
+
+        // Split off possible leading "http:", "mailto:", etc.
+        // Cannot contain escaped characters.
+        let (scheme, rest) = super::getscheme(rawurl)?;
+        out.scheme = scheme;
+
+        let mut rest = if rest.ends_with('?') && (rest.chars().filter(|&v| v == '?').count() == 1) {
+            out.force_query = true;
+            rest.trim_end_matches('?')
+        } else {
+            let (r, raw_query) = super::split(rest, '?', true);
+            out.raw_query = raw_query;
+            r
+        };
+
+        if !rest.starts_with('/') {
+            if !out.scheme.is_empty() {
+                // We consider rootless paths per RFC 3986 as opaque.
+                out.opaque = rest;
+                return Ok(out);
+            }
+
+            if via_request {
+                let kind = ParseErrorKind::InvalidRequestURI;
+                return Err(ParseError::new(kind, super::offset(rawurl, rest)));
+            }
+
+            // Avoid confusion with malformed schemes, like cache_object:foo/bar.
+            // See golang.org/issue/16822.
+            //
+            // RFC 3986, §3.3:
+            // In addition, a URI reference (Section 4.1) may be a relative-path reference,
+            // in which case the first path segment cannot contain a colon (":") character.
+            if let Some(colon) = rest.find(':') {
+                let slash = rest.find('/');
+                if slash.is_none() || colon < slash.unwrap() {
+                    let kind = ParseErrorKind::ColonInFirstSegment;
+                    return Err(ParseError::new(kind, super::offset(rawurl, rest) + colon));
+                }
+            }
+        }
+
+        if (!out.scheme.is_empty() || (!via_request && !rest.starts_with("///")))
+            && rest.starts_with("//")
+        {
+            let (authority, r) = super::split(rest.get(2..).unwrap(), '/', false);
+            let (user, host) = super::check_authority(authority)
+                .map_err(|err| err.at(super::offset(rawurl, authority)))?;
+            out.user = user;
+            out.host = host;
+            rest = r;
+        }
+
+        super::validate(rest, Encoding::Path).map_err(|err| err.at(super::offset(rawurl, rest)))?;
+        out.path = rest;
+
+        Ok(out)
+    }
+}
+
+impl<'a> From<URLRef<'a>> for URL {
+    fn from(u: URLRef<'a>) -> Self {
+        u.to_url()
+    }
+}
+
+const VALIDATED: &str = "escapes validated on parsing";
+
+/// unescaped unescapes s, whose escapes were validated on parsing, replacing
+/// invalid UTF-8.
+fn unescaped(s: &str, mode: Encoding) -> Cow<'_, str> {
+    internal::unescape_lossy(s, mode).expect(VALIDATED)
+}
@@ -18,12 +18,13 @@ impl fmt::Display for UserInfo {
     // fmt returns the encoded userinfo information in the standard form
     // of "username[:password]".
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut s = internal::escape(&self.name, Encoding::UserPassword);
+        internal::escape_into(self.name.as_bytes(), Encoding::UserPassword, f)?;
         if let Some(v) = &self.password {
-            s += format!(":{}", internal::escape(&v, Encoding::UserPassword)).as_str();
+            f.write_str(":")?;
+            internal::escape_into(v.as_bytes(), Encoding::UserPassword, f)?;
         }
 
-        write!(f, "{}", s)
+        Ok(())
     }
 }
 
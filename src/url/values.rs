@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 
-use super::errors::Error;
+use super::errors::{self, Error, LimitExceeded};
+use super::internal::{self, Encoding};
+use super::Limits;
 
 /// Values maps a string key to a list of values.
 /// It is typically used for query parameters and form values.
@@ -67,17 +69,14 @@ impl Values {
 
         let mut out = String::new();
         for k in keys {
-            let values = self.0.get(k).unwrap();
-            let key_escaped = super::query_escape(k);
-
-            for v in values {
-                if out.len() > 0 {
+            for v in self.0.get(k).unwrap() {
+                if !out.is_empty() {
                     out.push('&');
                 }
 
-                out.push_str(key_escaped.as_str());
+                let _ = super::query_escape_into(k, &mut out);
                 out.push('=');
-                out.push_str(super::query_escape(v).as_str());
+                let _ = super::query_escape_into(v, &mut out);
             }
         }
 
@@ -131,46 +130,60 @@ impl Values {
 /// }
 /// ```
 pub fn parse_query(query: &str) -> Result<Values, (Values, Error)> {
+    parse_query_with(query, &ParseQueryOptions::compat())
+}
+
+/// parse_query_with is like [parse_query](fn.parse_query.html) but splits and
+/// unescapes the query as told by options. In strict mode it stops at the
+/// first malformed pair, returning the values parsed before it.
+///
+/// # Example
+///
+/// ```
+/// use net::url::{self, ParseQueryOptions, Semicolons};
+///
+/// // Go 1.17 and later reject semicolons, which some proxies take as
+/// // separators, so "a=1;b=2" can't smuggle b past them.
+/// let (v, err) = url::parse_query_with("a=1;b=2&c=3", &ParseQueryOptions::default()).unwrap_err();
+/// assert_eq!(Some("3"), v.get("c"));
+/// assert_eq!(None, v.get("a"));
+/// assert_eq!("net/url: invalid semicolon separator in query", err.to_string());
+///
+/// let options = ParseQueryOptions {
+///     separators: vec![','],
+///     semicolons: Semicolons::Literal,
+///     plus_as_space: false,
+///     ..Default::default()
+/// };
+/// let v = url::parse_query_with("a=1+1;x,b=2", &options).unwrap();
+/// assert_eq!(Some("1+1;x"), v.get("a"));
+/// assert_eq!(Some("2"), v.get("b"));
+/// ```
+pub fn parse_query_with(
+    query: &str,
+    options: &ParseQueryOptions,
+) -> Result<Values, (Values, Error)> {
     let mut err: Option<Error> = None;
     let mut out = Values(HashMap::new());
 
-    let kv = query
-        .split(|v| v == '&' || v == ';')
-        .filter(|v| v.len() != 0)
-        .map(|v| v.splitn(2, '=').collect::<Vec<_>>());
-
-    for x in kv {
-        let (k, v) = if x.len() == 1 {
-            (x[0], "")
-        } else {
-            (x[0], x[1])
-        };
-
-        let kk = match super::query_unescape(k) {
-            Ok(v) => v,
-            Err(e) => {
-                if err.is_none() {
-                    err = Some(e);
-                }
-                continue;
-            }
-        };
+    let mut budget = QueryBudget::new(&options.limits);
+    for kv in query_pairs(query, options) {
+        if let Err(e) = budget.spend(&kv) {
+            return Err((out, e));
+        }
 
-        let vv = match super::query_unescape(v) {
-            Ok(v) => v,
-            Err(e) => {
-                if err.is_none() {
-                    err = Some(e);
+        match kv {
+            Ok((k, v)) => {
+                let limit = options.limits.max_values_per_key;
+                if out.0.get(&k).map_or(0, Vec::len) >= limit {
+                    return Err((out, LimitExceeded::ValuesPerKey { key: k, limit }.into()));
                 }
-                continue;
+                out.0.entry(k).or_default().push(v)
             }
-        };
-
-        if !out.0.contains_key(&kk) {
-            out.0.insert(kk.to_string(), Vec::new());
+            Err(e) if options.strict => return Err((out, e)),
+            Err(e) if err.is_none() => err = Some(e),
+            Err(_) => {}
         }
-
-        out.0.get_mut(&kk).unwrap().push(vv);
     }
 
     match err {
@@ -178,3 +191,139 @@ pub fn parse_query(query: &str) -> Result<Values, (Values, Error)> {
         None => Ok(out),
     }
 }
+
+/// ParseQueryOptions tells how [parse_query_with](fn.parse_query_with.html)
+/// splits a query into key-value pairs and unescapes them. The default follows
+/// Go 1.17 and later, rejecting semicolons, while
+/// [parse_query](fn.parse_query.html) splits on them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseQueryOptions {
+    /// characters separating the pairs, other than ';' which is handled by
+    /// semicolons
+    pub separators: Vec<char>,
+    /// how semicolons are handled
+    pub semicolons: Semicolons,
+    /// whether '+' is unescaped to a space, as in HTML forms
+    pub plus_as_space: bool,
+    /// whether to keep pairs with an empty key, as "=v"
+    pub keep_empty_keys: bool,
+    /// whether to stop at the first malformed pair instead of skipping it
+    pub strict: bool,
+    /// bounds on the pairs and their unescaped size, which always stop parsing
+    /// when exceeded
+    pub limits: Limits,
+}
+
+impl ParseQueryOptions {
+    /// compat returns the options of parse_query, which splits on semicolons
+    /// and sets no limits.
+    pub(crate) fn compat() -> Self {
+        Self {
+            semicolons: Semicolons::Split,
+            limits: Limits::NONE,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for ParseQueryOptions {
+    /// default splits on '&' only, rejects semicolons, unescapes '+' to a
+    /// space, keeps empty keys, skips malformed pairs and applies the default
+    /// limits.
+    fn default() -> Self {
+        Self {
+            separators: vec!['&'],
+            semicolons: Semicolons::Reject,
+            plus_as_space: true,
+            keep_empty_keys: true,
+            strict: false,
+            limits: Limits::default(),
+        }
+    }
+}
+
+/// Semicolons tells how semicolons in a query are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Semicolons {
+    /// semicolons are kept in keys and values
+    Literal,
+    /// a pair with a semicolon is malformed
+    Reject,
+    /// semicolons separate pairs, as ampersands do
+    Split,
+}
+
+/// QueryBudget accounts the pairs parsed from a query against the limits on
+/// their number and unescaped size.
+pub(crate) struct QueryBudget<'a> {
+    limits: &'a Limits,
+    pairs: usize,
+    decoded: usize,
+}
+
+impl<'a> QueryBudget<'a> {
+    pub(crate) fn new(limits: &'a Limits) -> Self {
+        Self {
+            limits,
+            pairs: 0,
+            decoded: 0,
+        }
+    }
+
+    /// spend accounts for the next item of query_pairs, malformed pairs
+    /// included, and reports an error once a limit is exceeded.
+    pub(crate) fn spend(&mut self, kv: &Result<(String, String), Error>) -> Result<(), Error> {
+        self.pairs += 1;
+        if self.pairs > self.limits.max_query_pairs {
+            return Err(LimitExceeded::QueryPairs(self.limits.max_query_pairs).into());
+        }
+
+        if let Ok((k, v)) = kv {
+            self.decoded = self.decoded.saturating_add(k.len() + v.len());
+            if self.decoded > self.limits.max_decoded_len {
+                return Err(LimitExceeded::DecodedLength(self.limits.max_decoded_len).into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// query_pairs splits the URL-encoded query string into its key=value settings,
+/// separated as told by options, and unescapes them in order. A setting
+/// without an equals sign is a key set to an empty value.
+pub(crate) fn query_pairs<'a>(
+    query: &'a str,
+    options: &'a ParseQueryOptions,
+) -> impl Iterator<Item = Result<(String, String), Error>> + 'a {
+    let mode = if options.plus_as_space {
+        Encoding::QueryComponent
+    } else {
+        Encoding::PathSegment
+    };
+
+    query
+        .split(move |c| match c {
+            ';' => options.semicolons == Semicolons::Split,
+            c => options.separators.contains(&c),
+        })
+        .filter(|v| !v.is_empty())
+        .map(move |kv| {
+            if options.semicolons == Semicolons::Reject && kv.contains(';') {
+                let err = "net/url: invalid semicolon separator in query";
+                return Err(errors::new_misc(err));
+            }
+
+            let mut x = kv.splitn(2, '=');
+            let (k, v) = (x.next().unwrap(), x.next().unwrap_or_default());
+
+            let k = internal::unescape(k, mode)?.into_owned();
+            let v = internal::unescape(v, mode)?.into_owned();
+
+            Ok((k, v))
+        })
+        .filter(move |kv| match kv {
+            Ok((k, _)) => options.keep_empty_keys || !k.is_empty(),
+            Err(_) => true,
+        })
+}
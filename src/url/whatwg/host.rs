@@ -0,0 +1,313 @@
+use super::super::errors::{self, Error};
+use super::super::idna;
+use super::{percent_decode, utf8_percent_encode, C0_CONTROL};
+
+/// parse_host implements the host parser of the URL Standard, returning the
+/// serialized host.
+pub(super) fn parse_host(input: &str, is_opaque: bool) -> Result<String, Error> {
+    if let Some(v) = input.strip_prefix('[') {
+        let v = v
+            .strip_suffix(']')
+            .ok_or_else(|| failure("IPv6-unclosed", input))?;
+        let pieces = parse_ipv6(v).ok_or_else(|| failure("IPv6-invalid", input))?;
+        return Ok(format!("[{}]", serialize_ipv6(&pieces)));
+    }
+
+    if is_opaque {
+        return parse_opaque_host(input);
+    }
+
+    let domain = String::from_utf8_lossy(&percent_decode(input)).into_owned();
+
+    let ascii_domain =
+        idna::to_ascii(&domain, false).map_err(|_| failure("domain-to-ASCII", input))?;
+    if ascii_domain.is_empty() || ascii_domain.chars().any(is_forbidden_domain_code_point) {
+        return Err(failure("domain-invalid-code-point", input));
+    }
+
+    if ends_in_a_number(&ascii_domain) {
+        let addr = parse_ipv4(&ascii_domain).ok_or_else(|| failure("IPv4-invalid", input))?;
+        return Ok(std::net::Ipv4Addr::from(addr).to_string());
+    }
+
+    Ok(ascii_domain)
+}
+
+/// ends_in_a_number reports whether the last non-empty label of the domain is
+/// a number, in which case the domain must be an IPv4 address.
+fn ends_in_a_number(domain: &str) -> bool {
+    let mut parts = domain.split('.').collect::<Vec<_>>();
+    if parts.last() == Some(&"") {
+        if parts.len() == 1 {
+            return false;
+        }
+        parts.pop();
+    }
+
+    let last = parts.last().unwrap();
+    if !last.is_empty() && last.bytes().all(|c| c.is_ascii_digit()) {
+        return true;
+    }
+
+    parse_ipv4_number(last).is_some()
+}
+
+fn failure(kind: &str, input: &str) -> Error {
+    errors::new_misc(format!("{} failure for host {:?}", kind, input))
+}
+
+fn is_forbidden_domain_code_point(c: char) -> bool {
+    is_forbidden_host_code_point(c) || c.is_ascii_control() || c == '%'
+}
+
+fn is_forbidden_host_code_point(c: char) -> bool {
+    std::matches!(
+        c,
+        '\0' | '\t'
+            | '\n'
+            | '\r'
+            | ' '
+            | '#'
+            | '/'
+            | ':'
+            | '<'
+            | '>'
+            | '?'
+            | '@'
+            | '['
+            | '\\'
+            | ']'
+            | '^'
+            | '|'
+    )
+}
+
+/// parse_ipv4 implements the IPv4 parser, accepting from one to four parts
+/// in decimal, octal or hexadecimal.
+fn parse_ipv4(input: &str) -> Option<u32> {
+    let mut parts = input.split('.').collect::<Vec<_>>();
+    if parts.last() == Some(&"") && parts.len() > 1 {
+        parts.pop();
+    }
+
+    if parts.len() > 4 {
+        return None;
+    }
+
+    let numbers = parts
+        .iter()
+        .map(|v| parse_ipv4_number(v))
+        .collect::<Option<Vec<_>>>()?;
+
+    let (last, init) = numbers.split_last().unwrap();
+    if init.iter().any(|&v| v > 255) || *last >= 256u64.pow(5 - numbers.len() as u32) {
+        return None;
+    }
+
+    let mut ipv4 = *last;
+    for (i, &v) in init.iter().enumerate() {
+        ipv4 += v << (8 * (3 - i));
+    }
+
+    Some(ipv4 as u32)
+}
+
+/// parse_ipv4_number parses one part of an IPv4 address, saturating values too
+/// large for any address.
+fn parse_ipv4_number(input: &str) -> Option<u64> {
+    if input.is_empty() {
+        return None;
+    }
+
+    let (input, radix) = if input.starts_with("0x") || input.starts_with("0X") {
+        (&input[2..], 16)
+    } else if input.len() > 1 && input.starts_with('0') {
+        (&input[1..], 8)
+    } else {
+        (input, 10)
+    };
+
+    if input.is_empty() {
+        return Some(0);
+    }
+
+    if !input.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+
+    // Digits are valid by now, so the only error left is an overflow.
+    Some(u64::from_str_radix(input, radix).unwrap_or(u64::MAX))
+}
+
+/// parse_ipv6 implements the IPv6 parser, returning the eight 16-bit pieces.
+fn parse_ipv6(input: &str) -> Option<[u16; 8]> {
+    let input = input.chars().collect::<Vec<_>>();
+    let c = |i: usize| input.get(i).copied();
+
+    let mut address = [0u16; 8];
+    let mut piece_index = 0;
+    let mut compress = None;
+    let mut pointer = 0;
+
+    if c(pointer) == Some(':') {
+        if c(pointer + 1) != Some(':') {
+            return None;
+        }
+        pointer += 2;
+        piece_index += 1;
+        compress = Some(piece_index);
+    }
+
+    while c(pointer).is_some() {
+        if piece_index == 8 {
+            return None;
+        }
+
+        if c(pointer) == Some(':') {
+            if compress.is_some() {
+                return None;
+            }
+            pointer += 1;
+            piece_index += 1;
+            compress = Some(piece_index);
+            continue;
+        }
+
+        let mut value = 0u16;
+        let mut length = 0;
+        while length < 4 {
+            match c(pointer).and_then(|v| v.to_digit(16)) {
+                Some(v) => value = value * 0x10 + v as u16,
+                None => break,
+            }
+            pointer += 1;
+            length += 1;
+        }
+
+        if c(pointer) == Some('.') {
+            if length == 0 {
+                return None;
+            }
+            pointer -= length;
+
+            if piece_index > 6 {
+                return None;
+            }
+
+            let mut numbers_seen = 0;
+            while c(pointer).is_some() {
+                let mut ipv4_piece: Option<u16> = None;
+                if numbers_seen > 0 {
+                    if c(pointer) == Some('.') && numbers_seen < 4 {
+                        pointer += 1;
+                    } else {
+                        return None;
+                    }
+                }
+
+                if !c(pointer)?.is_ascii_digit() {
+                    return None;
+                }
+
+                while let Some(number) = c(pointer).and_then(|v| v.to_digit(10)) {
+                    ipv4_piece = match ipv4_piece {
+                        None => Some(number as u16),
+                        Some(0) => return None,
+                        Some(v) => Some(v * 10 + number as u16),
+                    };
+                    if ipv4_piece > Some(255) {
+                        return None;
+                    }
+                    pointer += 1;
+                }
+
+                address[piece_index] = address[piece_index] * 0x100 + ipv4_piece.unwrap();
+                numbers_seen += 1;
+                if numbers_seen == 2 || numbers_seen == 4 {
+                    piece_index += 1;
+                }
+            }
+
+            if numbers_seen != 4 {
+                return None;
+            }
+            break;
+        } else if c(pointer) == Some(':') {
+            pointer += 1;
+            c(pointer)?;
+        } else if c(pointer).is_some() {
+            return None;
+        }
+
+        address[piece_index] = value;
+        piece_index += 1;
+    }
+
+    match compress {
+        Some(compress) => {
+            let mut swaps = piece_index - compress;
+            piece_index = 7;
+            while piece_index != 0 && swaps > 0 {
+                address.swap(piece_index, compress + swaps - 1);
+                piece_index -= 1;
+                swaps -= 1;
+            }
+        }
+        None if piece_index != 8 => return None,
+        None => {}
+    }
+
+    Some(address)
+}
+
+/// parse_opaque_host implements the opaque-host parser used by non-special
+/// schemes.
+fn parse_opaque_host(input: &str) -> Result<String, Error> {
+    if input
+        .chars()
+        .any(|c| c != '%' && is_forbidden_host_code_point(c))
+    {
+        return Err(failure("opaque-host-invalid-code-point", input));
+    }
+
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        utf8_percent_encode(&mut out, c, C0_CONTROL);
+    }
+
+    Ok(out)
+}
+
+/// serialize_ipv6 implements the IPv6 serializer, compressing the first
+/// longest run of two or more zero pieces.
+fn serialize_ipv6(pieces: &[u16; 8]) -> String {
+    let mut compress: Option<(usize, usize)> = None;
+    let mut i = 0;
+    while i < 8 {
+        let len = pieces[i..].iter().take_while(|&&v| v == 0).count();
+        if len > 1 && !std::matches!(compress, Some((_, n)) if n >= len) {
+            compress = Some((i, len));
+        }
+        i += len.max(1);
+    }
+
+    let mut out = String::new();
+    let mut i = 0;
+    while i < 8 {
+        match compress {
+            Some((start, len)) if start == i => {
+                out.push_str(if i == 0 { "::" } else { ":" });
+                i += len;
+            }
+            _ => {
+                out.push_str(&format!("{:x}", pieces[i]));
+                if i != 7 {
+                    out.push(':');
+                }
+                i += 1;
+            }
+        }
+    }
+
+    out
+}
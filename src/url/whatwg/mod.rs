@@ -0,0 +1,785 @@
+//! module whatwg parses URLs the way web browsers do, following the basic URL
+//! parser of the [URL Standard](https://url.spec.whatwg.org/).
+//!
+//! Unlike [parse](../fn.parse.html), which follows RFC 3986, the standard
+//! tolerates and repairs many inputs: tabs and newlines are stripped, backslashes
+//! act as slashes for special schemes, hosts are mapped through IDNA and numeric
+//! IPv4 forms such as `0x7f.1` are normalized, and default ports are elided.
+
+use std::fmt;
+
+use super::errors::{self, Error};
+
+mod host;
+
+// Percent-encode sets of the URL Standard, as predicates on the ASCII byte.
+type PercentEncodeSet = fn(u8) -> bool;
+
+const C0_CONTROL: PercentEncodeSet = |c| !(0x20..=0x7e).contains(&c);
+const FRAGMENT: PercentEncodeSet =
+    |c| C0_CONTROL(c) || std::matches!(c, b' ' | b'"' | b'<' | b'>' | b'`');
+const QUERY: PercentEncodeSet =
+    |c| C0_CONTROL(c) || std::matches!(c, b' ' | b'"' | b'#' | b'<' | b'>');
+const SPECIAL_QUERY: PercentEncodeSet = |c| QUERY(c) || c == b'\'';
+const PATH: PercentEncodeSet = |c| QUERY(c) || std::matches!(c, b'?' | b'`' | b'{' | b'}');
+const USERINFO: PercentEncodeSet =
+    |c| PATH(c) || std::matches!(c, b'/' | b':' | b';' | b'=' | b'@' | b'['..=b'^' | b'|');
+
+/// WhatwgURL is a URL record produced by [parse_whatwg](fn.parse_whatwg.html).
+/// Its components are kept in their serialized form and exposed through the
+/// getters of the URL Standard's API, e.g. [href](#method.href) and
+/// [pathname](#method.pathname).
+///
+/// # Example
+///
+/// ```
+/// let u = net::url::parse_whatwg("HTTP://EXAMPLE.com:80\\a\\..\\b?c d#e").unwrap();
+///
+/// assert_eq!("http://example.com/b?c%20d#e", u.href());
+/// assert_eq!("example.com", u.host());
+/// assert_eq!("", u.port());
+/// assert_eq!("/b", u.pathname());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WhatwgURL {
+    scheme: String,
+    username: String,
+    password: String,
+    host: Option<String>,
+    port: Option<u16>,
+    path: Path,
+    query: Option<String>,
+    fragment: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Path {
+    Opaque(String),
+    Segments(Vec<String>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    SchemeStart,
+    Scheme,
+    NoScheme,
+    SpecialRelativeOrAuthority,
+    PathOrAuthority,
+    Relative,
+    RelativeSlash,
+    SpecialAuthoritySlashes,
+    SpecialAuthorityIgnoreSlashes,
+    Authority,
+    Host,
+    Port,
+    File,
+    FileSlash,
+    FileHost,
+    PathStart,
+    Path,
+    OpaquePath,
+    Query,
+    Fragment,
+}
+
+impl WhatwgURL {
+    /// hash returns the fragment with a leading '#', or an empty string if the
+    /// fragment is absent or empty.
+    pub fn hash(&self) -> String {
+        match &self.fragment {
+            Some(v) if !v.is_empty() => format!("#{}", v),
+            _ => "".to_string(),
+        }
+    }
+
+    /// host returns the serialized host followed by the port if any.
+    pub fn host(&self) -> String {
+        match (&self.host, self.port) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.clone(),
+            (None, _) => "".to_string(),
+        }
+    }
+
+    /// hostname returns the serialized host, with IPv6 addresses in brackets.
+    pub fn hostname(&self) -> &str {
+        self.host.as_deref().unwrap_or_default()
+    }
+
+    /// href returns the serialization of the URL.
+    pub fn href(&self) -> String {
+        self.to_string()
+    }
+
+    /// parse parses a URL with the receiver as the base URL, like `new URL(input,
+    /// base)` in browsers.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let base = net::url::parse_whatwg("http://example.org/foo/bar").unwrap();
+    ///
+    /// let u = base.parse("\\\\server\\file").unwrap();
+    /// assert_eq!("http://server/file", u.href());
+    /// ```
+    pub fn parse(&self, input: &str) -> Result<Self, Error> {
+        basic_parse(input, Some(self)).map_err(|err| errors::wrap("parse", input, err))
+    }
+
+    /// password returns the percent-encoded password.
+    pub fn password(&self) -> &str {
+        &self.password
+    }
+
+    /// pathname returns the serialized path.
+    pub fn pathname(&self) -> String {
+        match &self.path {
+            Path::Opaque(v) => v.clone(),
+            Path::Segments(v) => v.iter().map(|v| format!("/{}", v)).collect(),
+        }
+    }
+
+    /// port returns the port, or an empty string if the port is absent or the
+    /// default one of the scheme.
+    pub fn port(&self) -> String {
+        self.port.map(|v| v.to_string()).unwrap_or_default()
+    }
+
+    /// protocol returns the scheme followed by ':'.
+    pub fn protocol(&self) -> String {
+        format!("{}:", self.scheme)
+    }
+
+    /// search returns the query with a leading '?', or an empty string if the
+    /// query is absent or empty.
+    pub fn search(&self) -> String {
+        match &self.query {
+            Some(v) if !v.is_empty() => format!("?{}", v),
+            _ => "".to_string(),
+        }
+    }
+
+    /// username returns the percent-encoded username.
+    pub fn username(&self) -> &str {
+        &self.username
+    }
+
+    fn has_opaque_path(&self) -> bool {
+        std::matches!(self.path, Path::Opaque(_))
+    }
+
+    fn is_special(&self) -> bool {
+        default_port(&self.scheme).is_some() || self.scheme == "file"
+    }
+
+    fn segments(&mut self) -> &mut Vec<String> {
+        match &mut self.path {
+            Path::Segments(v) => v,
+            Path::Opaque(_) => unreachable!("URL with opaque path has no segments"),
+        }
+    }
+
+    /// shorten_path removes the last path segment, but never the drive letter
+    /// of a file URL.
+    fn shorten_path(&mut self) {
+        let is_file = self.scheme == "file";
+        let path = self.segments();
+        if is_file && path.len() == 1 && is_normalized_windows_drive_letter(&path[0]) {
+            return;
+        }
+
+        path.pop();
+    }
+}
+
+impl Default for WhatwgURL {
+    fn default() -> Self {
+        Self {
+            scheme: "".to_string(),
+            username: "".to_string(),
+            password: "".to_string(),
+            host: None,
+            port: None,
+            path: Path::Segments(Vec::new()),
+            query: None,
+            fragment: None,
+        }
+    }
+}
+
+impl fmt::Display for WhatwgURL {
+    /// `fmt` implements the URL serializer of the URL Standard.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:", self.scheme)?;
+
+        match &self.host {
+            Some(host) => {
+                write!(f, "//")?;
+                if !self.username.is_empty() || !self.password.is_empty() {
+                    write!(f, "{}", self.username)?;
+                    if !self.password.is_empty() {
+                        write!(f, ":{}", self.password)?;
+                    }
+                    write!(f, "@")?;
+                }
+
+                write!(f, "{}", host)?;
+                if let Some(port) = self.port {
+                    write!(f, ":{}", port)?;
+                }
+            }
+            None => {
+                // Keep a path starting with an empty segment from reading as
+                // an authority.
+                if std::matches!(&self.path, Path::Segments(v) if v.len() > 1 && v[0].is_empty()) {
+                    write!(f, "/.")?;
+                }
+            }
+        }
+
+        write!(f, "{}", self.pathname())?;
+
+        if let Some(v) = &self.query {
+            write!(f, "?{}", v)?;
+        }
+
+        if let Some(v) = &self.fragment {
+            write!(f, "#{}", v)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// parse_whatwg parses an absolute URL per the URL Standard, as browsers do for
+/// `new URL(input)`. Use [WhatwgURL::parse](struct.WhatwgURL.html#method.parse)
+/// to resolve a relative URL against a base.
+///
+/// # Example
+///
+/// ```
+/// use net::url;
+///
+/// let u = url::parse_whatwg(" https://0x7f.1:443/a/./b/../c\t ").unwrap();
+/// assert_eq!("https://127.0.0.1/a/c", u.href());
+///
+/// assert!(url::parse_whatwg("/relative").is_err());
+/// ```
+pub fn parse_whatwg(input: &str) -> Result<WhatwgURL, Error> {
+    basic_parse(input, None).map_err(|err| errors::wrap("parse", input, err))
+}
+
+/// basic_parse implements the basic URL parser of the URL Standard, without
+/// state override.
+fn basic_parse(input: &str, base: Option<&WhatwgURL>) -> Result<WhatwgURL, Error> {
+    let input = input
+        .trim_matches(|c| c <= ' ')
+        .chars()
+        .filter(|&c| !std::matches!(c, '\t' | '\n' | '\r'))
+        .collect::<Vec<_>>();
+
+    let mut url = WhatwgURL::default();
+    let mut state = State::SchemeStart;
+    let mut buffer = String::new();
+    let mut at_sign_seen = false;
+    let mut inside_brackets = false;
+    let mut password_token_seen = false;
+
+    // pointer is one ahead of the code point being processed, so that "decrease
+    // pointer by 1" never underflows.
+    let mut pointer = 0;
+    while pointer <= input.len() {
+        let c = input.get(pointer).copied();
+        let remaining = input.get((pointer + 1)..).unwrap_or_default();
+
+        match state {
+            State::SchemeStart => match c {
+                Some(c) if c.is_ascii_alphabetic() => {
+                    buffer.push(c.to_ascii_lowercase());
+                    state = State::Scheme;
+                }
+                _ => {
+                    state = State::NoScheme;
+                    pointer = pointer.wrapping_sub(1);
+                }
+            },
+            State::Scheme => match c {
+                Some(c) if c.is_ascii_alphanumeric() || std::matches!(c, '+' | '-' | '.') => {
+                    buffer.push(c.to_ascii_lowercase());
+                }
+                Some(':') => {
+                    url.scheme = std::mem::take(&mut buffer);
+                    if url.scheme == "file" {
+                        state = State::File;
+                    } else if url.is_special() {
+                        state = match base {
+                            Some(base) if base.scheme == url.scheme => {
+                                State::SpecialRelativeOrAuthority
+                            }
+                            _ => State::SpecialAuthoritySlashes,
+                        };
+                    } else if remaining.first() == Some(&'/') {
+                        state = State::PathOrAuthority;
+                        pointer += 1;
+                    } else {
+                        url.path = Path::Opaque("".to_string());
+                        state = State::OpaquePath;
+                    }
+                }
+                _ => {
+                    buffer.clear();
+                    state = State::NoScheme;
+                    pointer = usize::MAX; // start over
+                }
+            },
+            State::NoScheme => {
+                let base = match base {
+                    Some(v) if !v.has_opaque_path() || c == Some('#') => v,
+                    _ => return Err(errors::new_misc("missing-scheme-non-relative-URL")),
+                };
+
+                if base.has_opaque_path() {
+                    url.scheme = base.scheme.clone();
+                    url.path = base.path.clone();
+                    url.query = base.query.clone();
+                    url.fragment = Some("".to_string());
+                    state = State::Fragment;
+                } else {
+                    state = if base.scheme != "file" {
+                        State::Relative
+                    } else {
+                        State::File
+                    };
+                    pointer = pointer.wrapping_sub(1);
+                }
+            }
+            State::SpecialRelativeOrAuthority => {
+                if c == Some('/') && remaining.first() == Some(&'/') {
+                    state = State::SpecialAuthorityIgnoreSlashes;
+                    pointer += 1;
+                } else {
+                    state = State::Relative;
+                    pointer = pointer.wrapping_sub(1);
+                }
+            }
+            State::PathOrAuthority => {
+                if c == Some('/') {
+                    state = State::Authority;
+                } else {
+                    state = State::Path;
+                    pointer = pointer.wrapping_sub(1);
+                }
+            }
+            State::Relative => {
+                let base = base.unwrap();
+                url.scheme = base.scheme.clone();
+                if c == Some('/') || (url.is_special() && c == Some('\\')) {
+                    state = State::RelativeSlash;
+                } else {
+                    url.username = base.username.clone();
+                    url.password = base.password.clone();
+                    url.host = base.host.clone();
+                    url.port = base.port;
+                    url.path = base.path.clone();
+                    url.query = base.query.clone();
+
+                    match c {
+                        Some('?') => {
+                            url.query = Some("".to_string());
+                            state = State::Query;
+                        }
+                        Some('#') => {
+                            url.fragment = Some("".to_string());
+                            state = State::Fragment;
+                        }
+                        Some(_) => {
+                            url.query = None;
+                            url.shorten_path();
+                            state = State::Path;
+                            pointer = pointer.wrapping_sub(1);
+                        }
+                        None => {}
+                    }
+                }
+            }
+            State::RelativeSlash => {
+                if url.is_special() && (c == Some('/') || c == Some('\\')) {
+                    state = State::SpecialAuthorityIgnoreSlashes;
+                } else if c == Some('/') {
+                    state = State::Authority;
+                } else {
+                    let base = base.unwrap();
+                    url.username = base.username.clone();
+                    url.password = base.password.clone();
+                    url.host = base.host.clone();
+                    url.port = base.port;
+                    state = State::Path;
+                    pointer = pointer.wrapping_sub(1);
+                }
+            }
+            State::SpecialAuthoritySlashes => {
+                if c == Some('/') && remaining.first() == Some(&'/') {
+                    pointer += 1;
+                } else {
+                    pointer = pointer.wrapping_sub(1);
+                }
+                state = State::SpecialAuthorityIgnoreSlashes;
+            }
+            State::SpecialAuthorityIgnoreSlashes => {
+                if c != Some('/') && c != Some('\\') {
+                    state = State::Authority;
+                    pointer = pointer.wrapping_sub(1);
+                }
+            }
+            State::Authority => match c {
+                Some('@') => {
+                    if at_sign_seen {
+                        buffer.insert_str(0, "%40");
+                    }
+                    at_sign_seen = true;
+
+                    for cp in buffer.chars() {
+                        if cp == ':' && !password_token_seen {
+                            password_token_seen = true;
+                            continue;
+                        }
+
+                        if password_token_seen {
+                            utf8_percent_encode(&mut url.password, cp, USERINFO);
+                        } else {
+                            utf8_percent_encode(&mut url.username, cp, USERINFO);
+                        }
+                    }
+                    buffer.clear();
+                }
+                None | Some('/') | Some('?') | Some('#') => {
+                    if at_sign_seen && buffer.is_empty() {
+                        return Err(errors::new_misc("host-missing"));
+                    }
+                    pointer = pointer.wrapping_sub(buffer.chars().count() + 1);
+                    buffer.clear();
+                    state = State::Host;
+                }
+                Some('\\') if url.is_special() => {
+                    if at_sign_seen && buffer.is_empty() {
+                        return Err(errors::new_misc("host-missing"));
+                    }
+                    pointer = pointer.wrapping_sub(buffer.chars().count() + 1);
+                    buffer.clear();
+                    state = State::Host;
+                }
+                Some(c) => buffer.push(c),
+            },
+            State::Host => {
+                let end = std::matches!(c, None | Some('/') | Some('?') | Some('#'))
+                    || (url.is_special() && c == Some('\\'));
+
+                if c == Some(':') && !inside_brackets {
+                    if buffer.is_empty() {
+                        return Err(errors::new_misc("host-missing"));
+                    }
+                    url.host = Some(host::parse_host(&buffer, !url.is_special())?);
+                    buffer.clear();
+                    state = State::Port;
+                } else if end {
+                    pointer = pointer.wrapping_sub(1);
+                    if url.is_special() && buffer.is_empty() {
+                        return Err(errors::new_misc("host-missing"));
+                    }
+                    url.host = Some(host::parse_host(&buffer, !url.is_special())?);
+                    buffer.clear();
+                    state = State::PathStart;
+                } else {
+                    let c = c.unwrap();
+                    if c == '[' {
+                        inside_brackets = true;
+                    } else if c == ']' {
+                        inside_brackets = false;
+                    }
+                    buffer.push(c);
+                }
+            }
+            State::Port => {
+                let end = std::matches!(c, None | Some('/') | Some('?') | Some('#'))
+                    || (url.is_special() && c == Some('\\'));
+
+                match c {
+                    Some(c) if c.is_ascii_digit() => buffer.push(c),
+                    _ if end => {
+                        if !buffer.is_empty() {
+                            let port = buffer
+                                .parse::<u16>()
+                                .map_err(|_| errors::new_misc("port-out-of-range"))?;
+                            url.port = if default_port(&url.scheme) == Some(port) {
+                                None
+                            } else {
+                                Some(port)
+                            };
+                            buffer.clear();
+                        }
+                        state = State::PathStart;
+                        pointer = pointer.wrapping_sub(1);
+                    }
+                    _ => return Err(errors::new_misc("port-invalid")),
+                }
+            }
+            State::File => {
+                url.scheme = "file".to_string();
+                url.host = Some("".to_string());
+
+                match (c, base) {
+                    (Some('/'), _) | (Some('\\'), _) => state = State::FileSlash,
+                    (_, Some(base)) if base.scheme == "file" => {
+                        url.host = base.host.clone();
+                        url.path = base.path.clone();
+                        url.query = base.query.clone();
+
+                        match c {
+                            Some('?') => {
+                                url.query = Some("".to_string());
+                                state = State::Query;
+                            }
+                            Some('#') => {
+                                url.fragment = Some("".to_string());
+                                state = State::Fragment;
+                            }
+                            Some(_) => {
+                                url.query = None;
+                                if !starts_with_windows_drive_letter(&input[pointer..]) {
+                                    url.shorten_path();
+                                } else {
+                                    url.path = Path::Segments(Vec::new());
+                                }
+                                state = State::Path;
+                                pointer = pointer.wrapping_sub(1);
+                            }
+                            None => {}
+                        }
+                    }
+                    _ => {
+                        state = State::Path;
+                        pointer = pointer.wrapping_sub(1);
+                    }
+                }
+            }
+            State::FileSlash => {
+                if c == Some('/') || c == Some('\\') {
+                    state = State::FileHost;
+                } else {
+                    if let Some(base) = base.filter(|v| v.scheme == "file") {
+                        url.host = base.host.clone();
+
+                        let rest = input.get(pointer..).unwrap_or_default();
+                        match &base.path {
+                            Path::Segments(v)
+                                if !starts_with_windows_drive_letter(rest)
+                                    && !v.is_empty()
+                                    && is_normalized_windows_drive_letter(&v[0]) =>
+                            {
+                                url.segments().push(v[0].clone());
+                            }
+                            _ => {}
+                        }
+                    }
+                    state = State::Path;
+                    pointer = pointer.wrapping_sub(1);
+                }
+            }
+            State::FileHost => match c {
+                None | Some('/') | Some('\\') | Some('?') | Some('#') => {
+                    pointer = pointer.wrapping_sub(1);
+                    if is_windows_drive_letter(&buffer) {
+                        // The buffer is kept as the first path segment.
+                        state = State::Path;
+                    } else if buffer.is_empty() {
+                        url.host = Some("".to_string());
+                        state = State::PathStart;
+                    } else {
+                        let mut host = host::parse_host(&buffer, !url.is_special())?;
+                        if host == "localhost" {
+                            host.clear();
+                        }
+                        url.host = Some(host);
+                        buffer.clear();
+                        state = State::PathStart;
+                    }
+                }
+                Some(c) => buffer.push(c),
+            },
+            State::PathStart => {
+                if url.is_special() {
+                    state = State::Path;
+                    if c != Some('/') && c != Some('\\') {
+                        pointer = pointer.wrapping_sub(1);
+                    }
+                } else if c == Some('?') {
+                    url.query = Some("".to_string());
+                    state = State::Query;
+                } else if c == Some('#') {
+                    url.fragment = Some("".to_string());
+                    state = State::Fragment;
+                } else if c.is_some() {
+                    state = State::Path;
+                    if c != Some('/') {
+                        pointer = pointer.wrapping_sub(1);
+                    }
+                }
+            }
+            State::Path => {
+                let slash = c == Some('/') || (url.is_special() && c == Some('\\'));
+                if slash || std::matches!(c, None | Some('?') | Some('#')) {
+                    if is_double_dot_segment(&buffer) {
+                        url.shorten_path();
+                        if !slash {
+                            url.segments().push("".to_string());
+                        }
+                    } else if is_single_dot_segment(&buffer) {
+                        if !slash {
+                            url.segments().push("".to_string());
+                        }
+                    } else {
+                        if url.scheme == "file"
+                            && url.segments().is_empty()
+                            && is_windows_drive_letter(&buffer)
+                        {
+                            buffer.replace_range(1.., ":");
+                        }
+                        url.segments().push(std::mem::take(&mut buffer));
+                    }
+                    buffer.clear();
+
+                    if c == Some('?') {
+                        url.query = Some("".to_string());
+                        state = State::Query;
+                    } else if c == Some('#') {
+                        url.fragment = Some("".to_string());
+                        state = State::Fragment;
+                    }
+                } else {
+                    utf8_percent_encode(&mut buffer, c.unwrap(), PATH);
+                }
+            }
+            State::OpaquePath => match c {
+                Some('?') => {
+                    url.query = Some("".to_string());
+                    state = State::Query;
+                }
+                Some('#') => {
+                    url.fragment = Some("".to_string());
+                    state = State::Fragment;
+                }
+                Some(c) => {
+                    if let Path::Opaque(path) = &mut url.path {
+                        utf8_percent_encode(path, c, C0_CONTROL);
+                    }
+                }
+                None => {}
+            },
+            State::Query => match c {
+                None | Some('#') => {
+                    let set = if url.is_special() {
+                        SPECIAL_QUERY
+                    } else {
+                        QUERY
+                    };
+                    let query = url.query.get_or_insert_with(String::new);
+                    for cp in buffer.chars() {
+                        utf8_percent_encode(query, cp, set);
+                    }
+                    buffer.clear();
+
+                    if c == Some('#') {
+                        url.fragment = Some("".to_string());
+                        state = State::Fragment;
+                    }
+                }
+                Some(c) => buffer.push(c),
+            },
+            State::Fragment => {
+                if let Some(c) = c {
+                    let fragment = url.fragment.get_or_insert_with(String::new);
+                    utf8_percent_encode(fragment, c, FRAGMENT);
+                }
+            }
+        }
+
+        pointer = pointer.wrapping_add(1);
+    }
+
+    Ok(url)
+}
+
+/// default_port returns the default port of special schemes other than "file".
+fn default_port(scheme: &str) -> Option<u16> {
+    match scheme {
+        "ftp" => Some(21),
+        "http" | "ws" => Some(80),
+        "https" | "wss" => Some(443),
+        _ => None,
+    }
+}
+
+fn is_double_dot_segment(s: &str) -> bool {
+    std::matches!(
+        s.to_ascii_lowercase().as_str(),
+        ".." | ".%2e" | "%2e." | "%2e%2e"
+    )
+}
+
+fn is_normalized_windows_drive_letter(s: &str) -> bool {
+    is_windows_drive_letter(s) && s.ends_with(':')
+}
+
+fn is_single_dot_segment(s: &str) -> bool {
+    s == "." || s.eq_ignore_ascii_case("%2e")
+}
+
+fn is_windows_drive_letter(s: &str) -> bool {
+    let s = s.as_bytes();
+    s.len() == 2 && s[0].is_ascii_alphabetic() && (s[1] == b':' || s[1] == b'|')
+}
+
+/// percent_decode decodes the %XX escapes of s, leaving malformed ones as is.
+fn percent_decode(s: &str) -> Vec<u8> {
+    let s = s.as_bytes();
+    let hex = |c: u8| (c as char).to_digit(16).map(|v| v as u8);
+
+    let mut out = Vec::with_capacity(s.len());
+    let mut i = 0;
+    while i < s.len() {
+        let c = s[i];
+        if c == b'%' && i + 2 < s.len() {
+            if let (Some(hi), Some(lo)) = (hex(s[i + 1]), hex(s[i + 2])) {
+                out.push(hi << 4 | lo);
+                i += 3;
+                continue;
+            }
+        }
+
+        out.push(c);
+        i += 1;
+    }
+
+    out
+}
+
+fn starts_with_windows_drive_letter(s: &[char]) -> bool {
+    s.len() >= 2
+        && s[0].is_ascii_alphabetic()
+        && (s[1] == ':' || s[1] == '|')
+        && (s.len() == 2 || std::matches!(s[2], '/' | '\\' | '?' | '#'))
+}
+
+/// utf8_percent_encode appends the code point to out, percent-encoding the
+/// bytes of its UTF-8 encoding which are in the set.
+fn utf8_percent_encode(out: &mut String, c: char, set: PercentEncodeSet) {
+    if c.is_ascii() && !set(c as u8) {
+        out.push(c);
+        return;
+    }
+
+    let mut buf = [0u8; 4];
+    for &b in c.encode_utf8(&mut buf).as_bytes() {
+        out.push_str(&format!("%{:02X}", b));
+    }
+}